@@ -2,10 +2,29 @@ const COMMANDS: &[&str] = &[
   "register",
   "register_all",
   "update",
+  "update_with_holes",
+  "update_svg_path",
+  "update_circle",
+  "update_ellipse",
+  "update_rounded_rect",
+  "translate",
+  "scale",
+  "rotate",
+  "transform",
+  "set_z_index",
+  "raise",
+  "lower",
+  "hit_test",
+  "exists",
+  "get",
+  "list",
+  "set_metadata",
   "hide",
   "show",
   "remove",
-  "clear"
+  "clear",
+  "set_normalization",
+  "set_coordinate_mode"
 ];
 
 fn main() {
@@ -2,9 +2,10 @@ import { invoke } from '@tauri-apps/api/core'
 import { listen } from '@tauri-apps/api/event';
 
 
-interface Response {
+interface Response<T = undefined> {
   ok: boolean,
   error?: string,
+  data?: T,
 }
 
 async function register(id: string): Promise<Response> {
@@ -42,10 +43,180 @@ async function hide(id: string): Promise<Response> {
 }
 
 type points = [number, number];
-async function update(id: string, points: [points, points, points, ...points[]]): Promise<Response> {
+type ring = [points, points, points, ...points[]];
+type FillRule = 'evenodd' | 'nonzero';
+type CoordinateMode = 'widthNormalized' | 'fraction' | 'logical' | 'physical';
+type Matrix = [number, number, number, number, number, number];
+
+export type Shape =
+  | { type: 'polygon', rings: points[][], holes: points[][] }
+  | { type: 'circle', center: points, radius: number }
+  | { type: 'ellipse', center: points, radiusX: number, radiusY: number }
+  | { type: 'roundedRect', x: number, y: number, width: number, height: number, radius: number };
+
+export interface PolygonInfo {
+  id: string,
+  /** In the same units as `update` */
+  shape: Shape,
+  visible: boolean,
+  hovered: boolean,
+  fillRule: FillRule,
+  padding: number,
+  zIndex: number,
+  metadata: unknown,
+}
+
+interface UpdateOptions {
+  fillRule?: FillRule,
+  /** Grow (positive) or shrink (negative) the hit area, in normalized units. */
+  padding?: number,
+}
+
+async function update(id: string, points: ring | ring[], options: UpdateOptions = {}): Promise<Response> {
   return await invoke<Response>('plugin:polygon|update', {
     id,
-    points
+    points,
+    ...options
+  }).then((r: Response) => r);
+}
+
+async function updateWithHoles(id: string, points: ring | ring[], holes: ring[]): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|update_with_holes', {
+    id,
+    points,
+    holes
+  }).then((r: Response) => r);
+}
+async function updateSvgPath(id: string, path: string, tolerance?: number, options: UpdateOptions = {}): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|update_svg_path', {
+    id,
+    path,
+    tolerance,
+    ...options
+  }).then((r: Response) => r);
+}
+
+async function updateCircle(id: string, center: points, radius: number): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|update_circle', {
+    id,
+    center,
+    radius
+  }).then((r: Response) => r);
+}
+
+async function updateEllipse(id: string, center: points, radiusX: number, radiusY: number): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|update_ellipse', {
+    id,
+    center,
+    radiusX,
+    radiusY
+  }).then((r: Response) => r);
+}
+
+async function updateRoundedRect(id: string, position: points, size: points, radius: number): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|update_rounded_rect', {
+    id,
+    position,
+    size,
+    radius
+  }).then((r: Response) => r);
+}
+
+async function translate(id: string, dx: number, dy: number): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|translate', {
+    id,
+    dx,
+    dy
+  }).then((r: Response) => r);
+}
+
+async function scale(id: string, sx: number, sy: number, origin: points): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|scale', {
+    id,
+    sx,
+    sy,
+    origin
+  }).then((r: Response) => r);
+}
+
+/** Positive angles turn clockwise on screen, as with CSS, without shearing in `'fraction'` mode. */
+async function rotate(id: string, degrees: number, origin: points): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|rotate', {
+    id,
+    degrees,
+    origin
+  }).then((r: Response) => r);
+}
+
+/** `matrix` is `[a, b, c, d, e, f]`, in the same order as CSS `matrix()`. Use `rotate` to rotate in `'fraction'` mode. */
+async function transform(id: string, matrix: Matrix): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|transform', {
+    id,
+    matrix
+  }).then((r: Response) => r);
+}
+
+async function setZIndex(id: string, zIndex: number): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|set_z_index', {
+    id,
+    zIndex
+  }).then((r: Response) => r);
+}
+
+async function raise(id: string): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|raise', {
+    id
+  }).then((r: Response) => r);
+}
+
+async function lower(id: string): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|lower', {
+    id
+  }).then((r: Response) => r);
+}
+
+/** Ids of polygons at the position, the topmost first. */
+async function hitTest(x: number, y: number): Promise<Response<string[]>> {
+  return await invoke<Response<string[]>>('plugin:polygon|hit_test', {
+    x,
+    y
+  }).then((r: Response<string[]>) => r);
+}
+
+async function exists(id: string): Promise<Response<boolean>> {
+  return await invoke<Response<boolean>>('plugin:polygon|exists', {
+    id
+  }).then((r: Response<boolean>) => r);
+}
+
+async function get(id: string): Promise<Response<PolygonInfo>> {
+  return await invoke<Response<PolygonInfo>>('plugin:polygon|get', {
+    id
+  }).then((r: Response<PolygonInfo>) => r);
+}
+
+/** All polygons, the topmost first. */
+async function list(): Promise<Response<PolygonInfo[]>> {
+  return await invoke<Response<PolygonInfo[]>>('plugin:polygon|list', {}).then((r: Response<PolygonInfo[]>) => r);
+}
+
+/** Pass `null` to remove the metadata. */
+async function setMetadata(id: string, metadata: unknown): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|set_metadata', {
+    id,
+    metadata
+  }).then((r: Response) => r);
+}
+
+async function setNormalization(enabled: boolean): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|set_normalization', {
+    enabled
+  }).then((r: Response) => r);
+}
+
+async function setCoordinateMode(mode: CoordinateMode): Promise<Response> {
+  return await invoke<Response>('plugin:polygon|set_coordinate_mode', {
+    mode
   }).then((r: Response) => r);
 }
 
@@ -55,18 +226,42 @@ const POLYGON_DOUBLE_CLICK = "POLYGON_DOUBLE_CLICK";
 const POLYGON_RIGHT_CLICK = "POLYGON_RIGHT_CLICK";
 const POLYGON_DRAG = "POLYGON_DRAG";
 const POLYGON_MOUSE_MOVE = "POLYGON_MOUSE_MOVE";
+const POLYGON_MOUSE_ENTER = "POLYGON_MOUSE_ENTER";
+const POLYGON_MOUSE_LEAVE = "POLYGON_MOUSE_LEAVE";
+const POLYGON_DISPLAY_CHANGED = "POLYGON_DISPLAY_CHANGED";
 const POLYGON_WHEEL = "POLYGON_WHEEL";
 const POLYGON_ERROR = "POLYGON_ERROR";
 
-type Payload = ClickPayload | DragPayload | ErrorPayload | WheelPayload | MovePayload;
+type Payload = ClickPayload | DragPayload | ErrorPayload | WheelPayload | MovePayload | EnterPayload | LeavePayload | DisplayPayload;
 type EventCallback = (payload: Payload) => void;
 
 export interface ClickPayload {
   position: { x: number, y: number },
+  /** The polygon on top at the cursor position, if any */
+  topmost: string | null,
+  /** Label of the window `topmost` belongs to, if any */
+  window: string | null,
+  /** Index of the monitor the cursor is on, if known */
+  monitor: number | null,
 }
 
 export interface MovePayload {
   position: { x: number, y: number },
+  /** Index of the monitor the cursor is on, if known */
+  monitor: number | null,
+}
+
+export interface EnterPayload {
+  /** Label of the window the polygons belong to */
+  window: string,
+  /** All polygons the cursor is in, the topmost first */
+  ids: string[],
+  topmost: string,
+}
+
+export interface LeavePayload {
+  /** Label of the window the cursor left the polygons of */
+  window: string,
 }
 
 export interface WheelPayload {
@@ -78,10 +273,19 @@ export interface DragPayload {
   to: { x: number, y: number }
 }
 
+export interface DisplayPayload {
+  /** Label of the window which changed */
+  window: string,
+  /** Size of the window in physical pixels */
+  width: number,
+  height: number,
+  scaleFactor: number,
+}
+
 export interface ErrorPayload {
   error: string
 }
-const Events = ["LeftClick", "DoubleClick", "RightClick", "Drag", "MouseMove", "Wheel", "Error"];
+const Events = ["LeftClick", "DoubleClick", "RightClick", "Drag", "MouseMove", "MouseEnter", "MouseLeave", "Wheel", "DisplayChanged", "Error"];
 
 const EventCallbacks = {
   LeftClick: [] as EventCallback[],
@@ -90,11 +294,17 @@ const EventCallbacks = {
   Drag: [] as EventCallback[],
   Wheel: [] as EventCallback[],
   MouseMove: [] as EventCallback[],
+  MouseEnter: [] as EventCallback[],
+  MouseLeave: [] as EventCallback[],
+  DisplayChanged: [] as EventCallback[],
   Error: [] as EventCallback[],
 }
 
 type ClickEvent = "LeftClick" | "DoubleClick" | "RightClick";
 type MoveEvent = "MouseMove";
+type EnterEvent = "MouseEnter";
+type LeaveEvent = "MouseLeave";
+type DisplayEvent = "DisplayChanged";
 type DragEvent = "Drag";
 type ErrorEvent = "Error";
 type WheelEvent = "Wheel";
@@ -104,7 +314,10 @@ function on(env: DragEvent, callback: (payload: DragPayload) => void): void;
 function on(env: ErrorEvent, callback: (payload: ErrorPayload) => void): void;
 function on(env: WheelEvent, callback: (payload: WheelPayload) => void): void;
 function on(env: MoveEvent, callback: (payload: MovePayload) => void): void;
-function on(evt: ClickEvent | DragEvent | ErrorEvent | WheelEvent | MoveEvent, callback: any) {
+function on(env: EnterEvent, callback: (payload: EnterPayload) => void): void;
+function on(env: LeaveEvent, callback: (payload: LeavePayload) => void): void;
+function on(env: DisplayEvent, callback: (payload: DisplayPayload) => void): void;
+function on(evt: ClickEvent | DragEvent | ErrorEvent | WheelEvent | MoveEvent | EnterEvent | LeaveEvent | DisplayEvent, callback: any) {
   if (!Events.includes(evt)) {
     throw new Error(`Event [${evt}] does not exist. Available event: ${Events.join(', ')}`);
   }
@@ -116,7 +329,10 @@ function off(env: DragEvent, callback: (payload: DragPayload) => void): void;
 function off(env: ErrorEvent, callback: (payload: ErrorPayload) => void): void;
 function off(env: WheelEvent, callback: (payload: WheelEvent) => void): void;
 function off(env: MoveEvent, callback: (payload: MovePayload) => void): void;
-function off(evt: ClickEvent | DragEvent | ErrorEvent | WheelEvent | MoveEvent, callback: any) {
+function off(env: EnterEvent, callback: (payload: EnterPayload) => void): void;
+function off(env: LeaveEvent, callback: (payload: LeavePayload) => void): void;
+function off(env: DisplayEvent, callback: (payload: DisplayPayload) => void): void;
+function off(evt: ClickEvent | DragEvent | ErrorEvent | WheelEvent | MoveEvent | EnterEvent | LeaveEvent | DisplayEvent, callback: any) {
   if (!Events.includes(evt)) {
     throw new Error(`Event [${evt}] does not exist. Available event: ${Events.join(', ')}`);
   }
@@ -147,6 +363,18 @@ listen(POLYGON_MOUSE_MOVE, async ev => {
   EventCallbacks.MouseMove.forEach(callback => callback(ev.payload as Payload));
 })
 
+listen(POLYGON_MOUSE_ENTER, async ev => {
+  EventCallbacks.MouseEnter.forEach(callback => callback(ev.payload as Payload));
+})
+
+listen(POLYGON_MOUSE_LEAVE, async ev => {
+  EventCallbacks.MouseLeave.forEach(callback => callback(ev.payload as Payload));
+})
+
+listen(POLYGON_DISPLAY_CHANGED, async ev => {
+  EventCallbacks.DisplayChanged.forEach(callback => callback(ev.payload as Payload));
+})
+
 listen(POLYGON_ERROR, async ev => {
   EventCallbacks.Error.forEach(callback => callback(ev.payload as Payload));
 })
@@ -159,6 +387,25 @@ export const polygon = {
   show,
   hide,
   update,
+  updateWithHoles,
+  updateSvgPath,
+  updateCircle,
+  updateEllipse,
+  updateRoundedRect,
+  translate,
+  scale,
+  rotate,
+  transform,
+  setZIndex,
+  raise,
+  lower,
+  hitTest,
+  exists,
+  get,
+  list,
+  setMetadata,
+  setNormalization,
+  setCoordinateMode,
   on,
   off
 }
\ No newline at end of file
@@ -1,12 +1,19 @@
 use tauri::AppHandle;
 use tauri::Runtime;
+use tauri::Window;
 
 use crate::models::*;
+use crate::polygon::{FillRule, Points, Transform};
+use crate::utils::CoordinateMode;
 use crate::PolygonExt;
 
 #[tauri::command]
-pub(crate) async fn register<R: Runtime>(app: AppHandle<R>, id: String) -> CommandResult {
-    match app.polygon().register(&id) {
+pub(crate) async fn register<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+) -> CommandResult {
+    match app.polygon().window(window.label()).register(&id) {
         Ok(()) => Response::ok(),
         Err(e) => Response::err(e),
     }
@@ -15,41 +22,54 @@ pub(crate) async fn register<R: Runtime>(app: AppHandle<R>, id: String) -> Comma
 #[tauri::command]
 pub(crate) async fn register_all<R: Runtime>(
     app: AppHandle<R>,
+    window: Window<R>,
     polygons: Vec<String>,
 ) -> CommandResult {
-    match app.polygon().register_all(polygons) {
+    match app.polygon().window(window.label()).register_all(polygons) {
         Ok(()) => Response::ok(),
         Err(e) => Response::err(e),
     }
 }
 
 #[tauri::command]
-pub(crate) async fn remove<R: Runtime>(app: AppHandle<R>, id: String) -> CommandResult {
-    match app.polygon().remove(&id) {
+pub(crate) async fn remove<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+) -> CommandResult {
+    match app.polygon().window(window.label()).remove(&id) {
         Ok(()) => Response::ok(),
         Err(e) => Response::err(e),
     }
 }
 
 #[tauri::command]
-pub(crate) async fn clear<R: Runtime>(app: AppHandle<R>) -> CommandResult {
-    match app.polygon().clear() {
+pub(crate) async fn clear<R: Runtime>(app: AppHandle<R>, window: Window<R>) -> CommandResult {
+    match app.polygon().window(window.label()).clear() {
         Ok(()) => Response::ok(),
         Err(e) => Response::err(e),
     }
 }
 
 #[tauri::command]
-pub(crate) async fn show<R: Runtime>(app: AppHandle<R>, id: String) -> CommandResult {
-    match app.polygon().show(&id) {
+pub(crate) async fn show<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+) -> CommandResult {
+    match app.polygon().window(window.label()).show(&id) {
         Ok(()) => Response::ok(),
         Err(e) => Response::err(e),
     }
 }
 
 #[tauri::command]
-pub(crate) async fn hide<R: Runtime>(app: AppHandle<R>, id: String) -> CommandResult {
-    match app.polygon().hide(&id) {
+pub(crate) async fn hide<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+) -> CommandResult {
+    match app.polygon().window(window.label()).hide(&id) {
         Ok(()) => Response::ok(),
         Err(e) => Response::err(e),
     }
@@ -58,11 +78,297 @@ pub(crate) async fn hide<R: Runtime>(app: AppHandle<R>, id: String) -> CommandRe
 #[tauri::command]
 pub(crate) async fn update<R: Runtime>(
     app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    points: Points,
+    fill_rule: Option<FillRule>,
+    padding: Option<f64>,
+) -> CommandResult {
+    match app
+        .polygon()
+        .window(window.label())
+        .update_with(&id, points, fill_rule, padding)
+    {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn update_with_holes<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    points: Points,
+    holes: Vec<Vec<(f64, f64)>>,
+) -> CommandResult {
+    match app
+        .polygon()
+        .window(window.label())
+        .update_with_holes(&id, points, holes)
+    {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn update_svg_path<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    path: String,
+    tolerance: Option<f64>,
+    fill_rule: Option<FillRule>,
+    padding: Option<f64>,
+) -> CommandResult {
+    match app
+        .polygon()
+        .window(window.label())
+        .update_svg_path_with(&id, &path, tolerance, fill_rule, padding)
+    {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn update_circle<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    center: (f64, f64),
+    radius: f64,
+) -> CommandResult {
+    match app
+        .polygon()
+        .window(window.label())
+        .update_circle(&id, center, radius)
+    {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn update_ellipse<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    center: (f64, f64),
+    radius_x: f64,
+    radius_y: f64,
+) -> CommandResult {
+    match app
+        .polygon()
+        .window(window.label())
+        .update_ellipse(&id, center, radius_x, radius_y)
+    {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn update_rounded_rect<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    position: (f64, f64),
+    size: (f64, f64),
+    radius: f64,
+) -> CommandResult {
+    match app
+        .polygon()
+        .window(window.label())
+        .update_rounded_rect(&id, position, size, radius)
+    {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn translate<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    dx: f64,
+    dy: f64,
+) -> CommandResult {
+    match app.polygon().window(window.label()).translate(&id, dx, dy) {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn scale<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    sx: f64,
+    sy: f64,
+    origin: (f64, f64),
+) -> CommandResult {
+    match app
+        .polygon()
+        .window(window.label())
+        .scale(&id, sx, sy, origin)
+    {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn rotate<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
     id: String,
-    points: Vec<(f64, f64)>,
+    degrees: f64,
+    origin: (f64, f64),
 ) -> CommandResult {
-    match app.polygon().update(&id, points) {
+    match app
+        .polygon()
+        .window(window.label())
+        .rotate(&id, degrees, origin)
+    {
         Ok(()) => Response::ok(),
         Err(e) => Response::err(e),
     }
 }
+
+#[tauri::command]
+pub(crate) async fn transform<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    matrix: Transform,
+) -> CommandResult {
+    match app.polygon().window(window.label()).transform(&id, matrix) {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn set_z_index<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    z_index: i32,
+) -> CommandResult {
+    match app
+        .polygon()
+        .window(window.label())
+        .set_z_index(&id, z_index)
+    {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn raise<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+) -> CommandResult {
+    match app.polygon().window(window.label()).raise(&id) {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn lower<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+) -> CommandResult {
+    match app.polygon().window(window.label()).lower(&id) {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn hit_test<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    x: f64,
+    y: f64,
+) -> CommandResult {
+    match app.polygon().window(window.label()).hit_test(x, y) {
+        Ok(ids) => Response::data(ids),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn exists<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+) -> CommandResult {
+    match app.polygon().window(window.label()).exists(&id) {
+        Ok(exists) => Response::data(exists),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn get<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+) -> CommandResult {
+    match app.polygon().window(window.label()).get(&id) {
+        Ok(info) => Response::data(info),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn list<R: Runtime>(app: AppHandle<R>, window: Window<R>) -> CommandResult {
+    match app.polygon().window(window.label()).list() {
+        Ok(list) => Response::data(list),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn set_metadata<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    id: String,
+    metadata: Option<serde_json::Value>,
+) -> CommandResult {
+    match app
+        .polygon()
+        .window(window.label())
+        .set_metadata(&id, metadata)
+    {
+        Ok(()) => Response::ok(),
+        Err(e) => Response::err(e),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn set_normalization<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+) -> CommandResult {
+    app.polygon().set_normalization(enabled);
+    Response::ok()
+}
+
+#[tauri::command]
+pub(crate) async fn set_coordinate_mode<R: Runtime>(
+    app: AppHandle<R>,
+    mode: CoordinateMode,
+) -> CommandResult {
+    app.polygon().set_coordinate_mode(mode);
+    Response::ok()
+}
@@ -1,42 +1,152 @@
 use log::{error, trace};
-use portable_atomic::AtomicPtr;
-use serde::de::DeserializeOwned;
 use std::fmt::Debug;
-use std::sync::atomic::Ordering;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
+use std::thread;
 use tauri::Manager;
-use tauri::{plugin::PluginApi, AppHandle, Runtime};
+use tauri::{plugin::PluginApi, AppHandle, Runtime, WebviewWindow};
 
-use crate::error::Result;
-use crate::utils::Convert;
+use crate::error::{Error, Result};
+use crate::grab::SystemClock;
+use crate::input::{InputBackend, InputMode, RdevBackend};
+use crate::models::{Config, PolygonInfo};
+use crate::polygon::{FillRule, Points, Shape, Transform};
+use crate::registry::Registry;
+use crate::state::State;
+use crate::svg;
+use crate::utils::{Convert, CoordinateMode};
 use crate::view;
 
-pub(crate) fn init<R: Runtime, C: DeserializeOwned>(
+/// Label of the window polygons belong to, if neither the builder nor the config sets one.
+pub(crate) const DEFAULT_WINDOW_LABEL: &str = "main";
+
+/// `windows` and `input_mode` given to the builder take precedence over the config.
+/// The first window is the default one.
+pub(crate) fn init<R: Runtime>(
     app: &AppHandle<R>,
-    _api: PluginApi<R, C>,
+    api: PluginApi<R, Option<Config>>,
     f: PolygonCallback<R>,
+    windows: Vec<String>,
+    backend: Option<Box<dyn InputBackend>>,
+    input_mode: Option<InputMode>,
 ) -> crate::Result<Polygon<R>> {
+    let mut window_labels = windows;
+    if window_labels.is_empty() {
+        if let Some(config) = api.config() {
+            window_labels.extend(config.window.clone());
+            window_labels.extend(config.windows.iter().cloned());
+        }
+    }
+    if window_labels.is_empty() {
+        window_labels.push(DEFAULT_WINDOW_LABEL.to_string());
+    }
+
+    let backend = backend.unwrap_or_else(|| {
+        let mode = input_mode
+            .or_else(|| api.config().as_ref().and_then(|config| config.input_mode))
+            .unwrap_or_default();
+        Box::new(RdevBackend::new(mode))
+    });
+
     Ok(Polygon {
         app_handle: app.clone(),
-        callback: AtomicPtr::new(Box::into_raw(Box::new(f))),
+        callback: dispatch(app.clone(), f)?,
+        state: Arc::new(State::new(backend)),
+        window_label: window_labels[0].clone(),
+        window_labels: Arc::new(window_labels),
     })
 }
 
 pub(crate) type PolygonCallback<R> =
     Box<dyn FnMut(&AppHandle<R>, crate::Event) + Send + Sync + 'static>;
 
+/// Run the callback on a thread of its own, which receives events in the order they are sent,
+/// until `None` is sent.
+///
+/// Events are emitted by the input hook and the main thread, which must not wait for the callback.
+/// Events the callback causes itself, e.g. errors of `Polygon` methods it calls, are queued.
+fn dispatch<R: Runtime>(
+    app: AppHandle<R>,
+    mut f: PolygonCallback<R>,
+) -> crate::Result<mpsc::Sender<Option<crate::Event>>> {
+    let (sender, receiver) = mpsc::channel::<Option<crate::Event>>();
+    thread::Builder::new()
+        .name("polygon-callback".into())
+        .spawn(move || {
+            while let Ok(Some(event)) = receiver.recv() {
+                f(&app, event);
+            }
+        })
+        .map_err(|e| Error::PluginInitializationError(e.to_string()))?;
+    Ok(sender)
+}
+
 /// Access to the Polygon APIs.
+///
+/// Each window has polygons of its own. Methods act on the default window,
+/// use `window` to access polygons of another one.
 pub struct Polygon<R: Runtime> {
     pub app_handle: AppHandle<R>,
-    callback: AtomicPtr<PolygonCallback<R>>,
+    /// Events for the thread running the callback, see `dispatch`
+    callback: mpsc::Sender<Option<crate::Event>>,
+    state: Arc<State>,
+    window_label: String,
+    window_labels: Arc<Vec<String>>,
 }
 
 impl<R: Runtime> Polygon<R> {
-    pub(crate) fn emit(&self, app_handle: &AppHandle<R>, event: crate::Event) {
-        let ptr = self.callback.load(Ordering::SeqCst);
-        let mut callback = unsafe { Box::from_raw(ptr) };
-        callback(app_handle, event);
-        self.callback
-            .store(Box::into_raw(callback), Ordering::SeqCst);
+    /// Queue the event for the callback, which sees events one at a time.
+    pub(crate) fn emit(&self, event: crate::Event) {
+        // fails once the plugin is dropped, when nobody is waiting for events anymore
+        let _ = self.callback.send(Some(event));
+    }
+    /// Label of the window methods act on, the default window unless returned by `window`.
+    pub fn window_label(&self) -> &str {
+        &self.window_label
+    }
+    /// Labels of all windows polygons can belong to, the default window first.
+    pub fn window_labels(&self) -> &[String] {
+        &self.window_labels
+    }
+    /// Access to polygons of the window by given label.
+    ///
+    /// Polygons, hover state and click-through are kept apart for each window,
+    /// so the same id can be registered in two windows.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().window("overlay-2").register("my-polygon")?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js, commands act on the window calling them
+    /// import { register } from 'tauri-plugin-polygon-api';
+    /// await register('my-polygon');
+    /// ```
+    pub fn window(&self, label: &str) -> Polygon<R> {
+        Polygon {
+            app_handle: self.app_handle.clone(),
+            callback: self.callback.clone(),
+            state: self.state.clone(),
+            window_label: label.to_string(),
+            window_labels: self.window_labels.clone(),
+        }
+    }
+    /// State shared by all windows, along with the event handlers of the plugin.
+    pub(crate) fn state(&self) -> &Arc<State> {
+        &self.state
+    }
+    fn webview_window(&self) -> Result<WebviewWindow<R>> {
+        self.app_handle
+            .get_webview_window(&self.window_label)
+            .ok_or_else(|| Error::WindowNotFound(self.window_label.clone()))
+    }
+    /// Polygons of the window, which is registered once the window is ready.
+    fn registry(&self) -> Result<Arc<Registry>> {
+        if !self.window_labels.contains(&self.window_label) {
+            return Err(Error::WindowNotFound(self.window_label.clone()));
+        }
+        self.state.registry(&self.window_label)
     }
     /// Register a default polygon with given id.
     ///
@@ -58,11 +168,14 @@ impl<R: Runtime> Polygon<R> {
     /// ```
     pub fn register(&self, id: &str) -> Result<()> {
         trace!("register: {id}");
-        match view::register(id.into()) {
+        match self
+            .registry()
+            .and_then(|registry| view::register(&registry, id.into()))
+        {
             Ok(_) => Ok(()),
             Err(e) => {
                 error!("register: {e}");
-                self.emit(&self.app_handle, crate::Event::Error(e.clone()));
+                self.emit(crate::Event::Error(e.clone()));
                 Err(e)
             }
         }
@@ -84,11 +197,16 @@ impl<R: Runtime> Polygon<R> {
     /// ```
     pub fn register_all<S: AsRef<str> + Debug>(&self, ids: Vec<S>) -> Result<()> {
         trace!("register_all: {ids:?}");
-        match view::register_all(ids.iter().map(|id| id.as_ref().to_string()).collect()) {
+        match self.registry().and_then(|registry| {
+            view::register_all(
+                &registry,
+                ids.iter().map(|id| id.as_ref().to_string()).collect(),
+            )
+        }) {
             Ok(_) => Ok(()),
             Err(e) => {
                 error!("register_all: {e}");
-                self.emit(&self.app_handle, crate::Event::Error(e.clone()));
+                self.emit(crate::Event::Error(e.clone()));
                 Err(e)
             }
         }
@@ -116,11 +234,14 @@ impl<R: Runtime> Polygon<R> {
     /// ```
     pub fn remove(&self, id: &str) -> Result<()> {
         trace!("remove: {id}");
-        match view::remove(&id) {
+        match self
+            .registry()
+            .and_then(|registry| view::remove(&registry, id))
+        {
             Ok(_) => Ok(()),
             Err(e) => {
                 error!("remove: {e}");
-                self.emit(&self.app_handle, crate::Event::Error(e.clone()));
+                self.emit(crate::Event::Error(e.clone()));
                 Err(e)
             }
         }
@@ -142,11 +263,14 @@ impl<R: Runtime> Polygon<R> {
     /// ```
     pub fn show(&self, id: &str) -> Result<()> {
         trace!("show: {id}");
-        match view::show(&id) {
+        match self
+            .registry()
+            .and_then(|registry| view::show(&registry, id))
+        {
             Ok(_) => Ok(()),
             Err(e) => {
                 error!("show: {e}");
-                self.emit(&self.app_handle, crate::Event::Error(e.clone()));
+                self.emit(crate::Event::Error(e.clone()));
                 Err(e)
             }
         }
@@ -154,7 +278,8 @@ impl<R: Runtime> Polygon<R> {
     /// Disable the polygon logically by given id.
     ///
     /// # Errors
-    /// This function will return an error if the `id` provided can not be found.
+    /// This function will return an error if the `id` provided can not be found,
+    /// or the window polygons belong to does not exist.
     ///
     /// # Example
     /// ```no_run
@@ -169,17 +294,14 @@ impl<R: Runtime> Polygon<R> {
     pub fn hide(&self, id: &str) -> Result<()> {
         trace!("hide: {id}");
 
-        self.app_handle
-            .get_webview_window("main")
-            .unwrap()
-            .set_ignore_cursor_events(true)
-            .unwrap();
-
-        match view::hide(&id) {
+        match self.registry().and_then(|registry| {
+            self.webview_window()?.set_ignore_cursor_events(true)?;
+            view::hide(&registry, id)
+        }) {
             Ok(_) => Ok(()),
             Err(e) => {
                 error!("hide: {e}");
-                self.emit(&self.app_handle, crate::Event::Error(e.clone()));
+                self.emit(crate::Event::Error(e.clone()));
                 Err(e)
             }
         }
@@ -192,14 +314,22 @@ impl<R: Runtime> Polygon<R> {
     ///    Position from 0 to 1, 0.1 means 10% of the `screen width`.
     /// 2. At least `3` points are required.
     /// 3. The order in which you define the points matters and can result in different shapes.
+    /// 4. Either a single ring or a list of disjoint rings can be given.
+    ///    All rings belong to the same polygon, so `MouseEnter` reports the `id` only once.
     ///
     /// # Errors
-    /// This function will return an error if the `id` provided can not be found.
+    /// This function will return an error if the `id` provided can not be found,
+    /// or the points are invalid (see `set_normalization` for details).
     ///
     /// # Example
     /// ```no_run
     /// // backend with rust
     /// app.polygon().update("my-polygon", vec![(0.0, 0.0), (0.1, 0.0), (0.1, 0.1), (0.0, 0.1)])?;
+    /// // multiple rings
+    /// app.polygon().update("my-polygon", vec![
+    ///     vec![(0.0, 0.0), (0.1, 0.0), (0.1, 0.1), (0.0, 0.1)],
+    ///     vec![(0.2, 0.0), (0.3, 0.0), (0.3, 0.1), (0.2, 0.1)],
+    /// ])?;
     /// ```
     /// ```javascript
     /// // frontend with js
@@ -215,24 +345,848 @@ impl<R: Runtime> Polygon<R> {
     ///     ]
     /// })
     /// ```
-    pub fn update(&self, id: &str, points: Vec<(f64, f64)>) -> Result<()> {
-        trace!("update: {id} - {points:?}");
-        match view::update(
-            &id,
-            &points
-                .iter()
-                .map(|(x, y)| Convert::from_viewport(*x, *y))
-                .collect::<Vec<(f64, f64)>>(),
-        ) {
+    pub fn update<P: Into<Points>>(&self, id: &str, points: P) -> Result<()> {
+        self.update_with(id, points.into(), None, None)
+    }
+    /// `update` along with `set_fill_rule` and `set_padding`,
+    /// none of which are applied unless the points are valid under the new fill rule.
+    pub(crate) fn update_with(
+        &self,
+        id: &str,
+        points: Points,
+        fill_rule: Option<FillRule>,
+        padding: Option<f64>,
+    ) -> Result<()> {
+        let rings = points.into_rings();
+        trace!("update: {id} - {rings:?}, {fill_rule:?}, {padding:?}");
+        match self.registry().and_then(|registry| {
+            let convert = self.state.convert(&registry.frame);
+            view::update_shape_with(
+                &registry,
+                id,
+                Shape::Polygon {
+                    rings: rings
+                        .iter()
+                        .map(|ring| convert.ring_from_viewport(ring))
+                        .collect(),
+                    holes: Vec::new(),
+                },
+                fill_rule,
+                padding.map(|padding| convert.length_from_viewport(padding)),
+                self.state.normalize_points(),
+            )
+        }) {
             Ok(_) => Ok(()),
             Err(e) => {
                 error!("update: {e}");
-                self.emit(&self.app_handle, crate::Event::Error(e.clone()));
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Update vertices of the polygon by given id, along with the holes inside it.
+    /// Within the outer ring, mouse events will not go through,
+    /// except for the areas enclosed by any of the holes.
+    ///
+    /// # Notice
+    /// 1. All positions should be converted to a `percentage based on the screen width`.
+    /// 2. At least `3` points are required for the outer ring and for each hole.
+    /// 3. Holes are expected to lie inside the outer ring.
+    ///    Like `update`, multiple outer rings can be given and the holes apply to all of them.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().update_with_holes(
+    ///     "my-polygon",
+    ///     vec![(0.0, 0.0), (0.3, 0.0), (0.3, 0.2), (0.0, 0.2)],
+    ///     vec![vec![(0.01, 0.01), (0.29, 0.01), (0.29, 0.19), (0.01, 0.19)]],
+    /// )?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    ///
+    /// await polygon.updateWithHoles('my-polygon',
+    ///     [[0, 0], [0.3, 0], [0.3, 0.2], [0, 0.2]],
+    ///     [[[0.01, 0.01], [0.29, 0.01], [0.29, 0.19], [0.01, 0.19]]]
+    /// )
+    /// ```
+    pub fn update_with_holes<P: Into<Points>>(
+        &self,
+        id: &str,
+        points: P,
+        holes: Vec<Vec<(f64, f64)>>,
+    ) -> Result<()> {
+        let rings = points.into().into_rings();
+        trace!("update_with_holes: {id} - {rings:?}, {holes:?}");
+        match self.registry().and_then(|registry| {
+            let convert = self.state.convert(&registry.frame);
+            view::update_with_holes(
+                &registry,
+                id,
+                &rings
+                    .iter()
+                    .map(|ring| convert.ring_from_viewport(ring))
+                    .collect::<Vec<_>>(),
+                &holes
+                    .iter()
+                    .map(|ring| convert.ring_from_viewport(ring))
+                    .collect::<Vec<_>>(),
+                self.state.normalize_points(),
+            )
+        }) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("update_with_holes: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Update the polygon by given id with SVG path data (the `d` attribute of `<path>`).
+    ///
+    /// Each subpath becomes a ring of the polygon, while Bézier curves and arcs are flattened into vertices.
+    ///
+    /// # Notice
+    /// 1. Path data should be converted to a `percentage based on the screen width`, same as `update`.
+    /// 2. `tolerance` is the maximum distance between a curve and the segments replacing it, in the same unit.
+    ///    Defaults to a quarter of a physical pixel if `None` is given.
+    /// 3. SVG fills paths with the non-zero rule by default, use `set_fill_rule` if needed.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found,
+    /// or the path data can not be parsed, or a curve needs more than 1024 segments to keep within `tolerance`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().update_svg_path("my-polygon", "M0.1 0.1 h0.1 a0.02 0.02 0 0 1 0 0.04 h-0.1 z", None)?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.updateSvgPath('my-polygon', 'M0.1 0.1 h0.1 a0.02 0.02 0 0 1 0 0.04 h-0.1 z');
+    /// ```
+    pub fn update_svg_path(&self, id: &str, path: &str, tolerance: Option<f64>) -> Result<()> {
+        self.update_svg_path_with(id, path, tolerance, None, None)
+    }
+    /// `update_svg_path` along with `set_fill_rule` and `set_padding`,
+    /// none of which are applied unless the path is valid under the new fill rule.
+    pub(crate) fn update_svg_path_with(
+        &self,
+        id: &str,
+        path: &str,
+        tolerance: Option<f64>,
+        fill_rule: Option<FillRule>,
+        padding: Option<f64>,
+    ) -> Result<()> {
+        trace!("update_svg_path: {id} - {path}, {tolerance:?}, {fill_rule:?}, {padding:?}");
+        match self.registry().and_then(|registry| {
+            let convert = self.state.convert(&registry.frame);
+            let tolerance =
+                tolerance.unwrap_or_else(|| convert.length_to_viewport(svg::DEFAULT_TOLERANCE));
+            let rings = svg::flatten(path, tolerance)?;
+            view::update_shape_with(
+                &registry,
+                id,
+                Shape::Polygon {
+                    rings: rings
+                        .iter()
+                        .map(|ring| convert.ring_from_viewport(ring))
+                        .collect(),
+                    holes: Vec::new(),
+                },
+                fill_rule,
+                padding.map(|padding| convert.length_from_viewport(padding)),
+                self.state.normalize_points(),
+            )
+        }) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("update_svg_path: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Move the polygon by given id by `(dx, dy)`.
+    ///
+    /// # Notice
+    /// `dx` and `dy` should be converted to a `percentage based on the screen width`.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().translate("my-polygon", 0.05, 0.0)?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.translate('my-polygon', 0.05, 0);
+    /// ```
+    pub fn translate(&self, id: &str, dx: f64, dy: f64) -> Result<()> {
+        self.transform(id, Transform::translate(dx, dy))
+    }
+    /// Scale the polygon by given id by `sx` and `sy` about `origin`.
+    ///
+    /// # Notice
+    /// `origin` should be converted to a `percentage based on the screen width`.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found,
+    /// or the scaled polygon has no area.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().scale("my-polygon", 2.0, 2.0, (0.5, 0.3))?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.scale('my-polygon', 2, 2, [0.5, 0.3]);
+    /// ```
+    pub fn scale(&self, id: &str, sx: f64, sy: f64, origin: (f64, f64)) -> Result<()> {
+        self.transform(id, Transform::scale(sx, sy, origin))
+    }
+    /// Rotate the polygon by given id by `degrees` about `origin`.
+    /// Positive angles turn clockwise on screen, as with CSS,
+    /// and the polygon keeps its shape even if the axes are scaled differently, e.g. in `Fraction` mode.
+    ///
+    /// # Notice
+    /// `origin` should be converted to a `percentage based on the screen width`.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().rotate("my-polygon", 45.0, (0.5, 0.3))?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.rotate('my-polygon', 45, [0.5, 0.3]);
+    /// ```
+    pub fn rotate(&self, id: &str, degrees: f64, origin: (f64, f64)) -> Result<()> {
+        trace!("rotate: {id} - {degrees} about {origin:?}");
+        self.transform_with(id, |convert| {
+            convert.rotation_from_viewport(degrees, origin)
+        })
+    }
+    /// Apply an affine transform to the polygon by given id.
+    ///
+    /// # Notice
+    /// 1. The translation `e` and `f` should be converted to a `percentage based on the screen width`.
+    /// 2. Circles, ellipses and rounded rects are turned into polygons when the transform
+    ///    can not keep their kind, e.g. a rotated ellipse.
+    /// 3. Padding is not transformed.
+    /// 4. The transform applies to coordinates of the current mode, so in `Fraction` mode
+    ///    on a window that is not square a rotation matrix becomes a shear, use `rotate` instead.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found,
+    /// or the transformed polygon does not pass validation.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// use tauri_plugin_polygon::Transform;
+    /// app.polygon().transform("my-polygon", Transform::from([1.0, 0.0, 0.5, 1.0, 0.0, 0.0]))?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.transform('my-polygon', [1, 0, 0.5, 1, 0, 0]);
+    /// ```
+    pub fn transform(&self, id: &str, transform: Transform) -> Result<()> {
+        trace!("transform: {id} - {transform:?}");
+        self.transform_with(id, |convert| convert.transform_from_viewport(&transform))
+    }
+    /// Apply the transform in physical pixels built by `f` from the conversion of the window.
+    fn transform_with(&self, id: &str, f: impl FnOnce(&Convert) -> Transform) -> Result<()> {
+        match self.registry().and_then(|registry| {
+            let convert = self.state.convert(&registry.frame);
+            view::transform(&registry, id, &f(&convert), self.state.normalize_points())
+        }) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("transform: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Set how the rings of the polygon by given id are filled.
+    ///
+    /// `FillRule::EvenOdd` is used by default, which turns self-overlapping areas into holes.
+    /// Use `FillRule::NonZero` to keep them filled, e.g. for outlines exported from design tools.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found,
+    /// or the polygon crosses itself, which is only allowed with `FillRule::NonZero`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// use tauri_plugin_polygon::FillRule;
+    /// app.polygon().set_fill_rule("my-polygon", FillRule::NonZero)?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.update('my-polygon', points, { fillRule: 'nonzero' });
+    /// ```
+    pub fn set_fill_rule(&self, id: &str, fill_rule: FillRule) -> Result<()> {
+        trace!("set_fill_rule: {id} - {fill_rule:?}");
+        match self
+            .registry()
+            .and_then(|registry| view::set_fill_rule(&registry, id, fill_rule))
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("set_fill_rule: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Grow or shrink the hit area of the polygon by given id.
+    ///
+    /// `padding` is in the same normalized units as points. A positive value extends the hit area
+    /// by that distance beyond the outline, with rounded corners; a negative value pulls it inside.
+    /// Useful for small icons which are hard to hit exactly. Defaults to `0.0`.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found,
+    /// or `padding` is not a finite number.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().set_padding("my-polygon", 0.005)?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.update('my-polygon', points, { padding: 0.005 });
+    /// ```
+    pub fn set_padding(&self, id: &str, padding: f64) -> Result<()> {
+        trace!("set_padding: {id} - {padding}");
+        match self.registry().and_then(|registry| {
+            let convert = self.state.convert(&registry.frame);
+            view::set_padding(&registry, id, convert.length_from_viewport(padding))
+        }) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("set_padding: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Set the z-index of the polygon by given id.
+    ///
+    /// Where polygons overlap, the one with the highest z-index is on top and is reported first,
+    /// e.g. as `topmost` of `Event::MouseEnter`. Between equal z-indexes, the one registered last is on top.
+    /// All polygons start with a z-index of `0`.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().set_z_index("my-polygon", 10)?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.setZIndex('my-polygon', 10);
+    /// ```
+    pub fn set_z_index(&self, id: &str, z_index: i32) -> Result<()> {
+        trace!("set_z_index: {id} - {z_index}");
+        match self
+            .registry()
+            .and_then(|registry| view::set_z_index(&registry, id, z_index))
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("set_z_index: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Put the polygon by given id above all other polygons, by raising its z-index.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().raise("my-polygon")?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.raise('my-polygon');
+    /// ```
+    pub fn raise(&self, id: &str) -> Result<()> {
+        trace!("raise: {id}");
+        match self
+            .registry()
+            .and_then(|registry| view::raise(&registry, id))
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("raise: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Put the polygon by given id below all other polygons, by lowering its z-index.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().lower("my-polygon")?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.lower('my-polygon');
+    /// ```
+    pub fn lower(&self, id: &str) -> Result<()> {
+        trace!("lower: {id}");
+        match self
+            .registry()
+            .and_then(|registry| view::lower(&registry, id))
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("lower: {e}");
+                self.emit(crate::Event::Error(e.clone()));
                 Err(e)
             }
         }
     }
-    /// Clear all polygons physically.
+    /// Ids of polygons at the given position, the topmost first, see `set_z_index`.
+    ///
+    /// Useful to find out what is under a point without moving the mouse, e.g. for tooltips.
+    /// Hidden polygons are skipped, padding is taken into account.
+    ///
+    /// # Notice
+    /// `x` and `y` should be converted to a `percentage based on the screen width`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// let ids = app.polygon().hit_test(0.5, 0.3)?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// const { data: ids } = await polygon.hitTest(0.5, 0.3);
+    /// ```
+    pub fn hit_test(&self, x: f64, y: f64) -> Result<Vec<String>> {
+        trace!("hit_test: ({x}, {y})");
+        match self.registry().and_then(|registry| {
+            let (x, y) = self
+                .state
+                .convert(&registry.frame)
+                .point_from_viewport(x, y);
+            view::hit_test(&registry, x, y)
+        }) {
+            Ok(ids) => Ok(ids),
+            Err(e) => {
+                error!("hit_test: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Whether a polygon with the given id is registered.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// if !app.polygon().exists("my-polygon")? {
+    ///     app.polygon().register("my-polygon")?;
+    /// }
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// const { data: exists } = await polygon.exists('my-polygon');
+    /// ```
+    pub fn exists(&self, id: &str) -> Result<bool> {
+        trace!("exists: {id}");
+        match self
+            .registry()
+            .and_then(|registry| view::exists(&registry, id))
+        {
+            Ok(exists) => Ok(exists),
+            Err(e) => {
+                error!("exists: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Get the current state of the polygon by given id.
+    ///
+    /// The shape and padding are returned in the same `percentage based on the screen width` as `update`.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// let info = app.polygon().get("my-polygon")?;
+    /// println!("{:?} visible: {}", info.shape, info.visible);
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// const { data: info } = await polygon.get('my-polygon');
+    /// ```
+    pub fn get(&self, id: &str) -> Result<PolygonInfo> {
+        trace!("get: {id}");
+        match self.registry().and_then(|registry| {
+            let info = view::get(&registry, id)?;
+            Ok(to_viewport(&self.state.convert(&registry.frame), info))
+        }) {
+            Ok(info) => Ok(info),
+            Err(e) => {
+                error!("get: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Get the current state of all registered polygons, the topmost first.
+    ///
+    /// See `get` for details.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// for info in app.polygon().list()? {
+    ///     println!("{}: hovered {}", info.id, info.hovered);
+    /// }
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// const { data: polygons } = await polygon.list();
+    /// ```
+    pub fn list(&self) -> Result<Vec<PolygonInfo>> {
+        trace!("list");
+        match self.registry().and_then(|registry| {
+            let convert = self.state.convert(&registry.frame);
+            let list = view::list(&registry)?;
+            Ok(list
+                .into_iter()
+                .map(|info| to_viewport(&convert, info))
+                .collect())
+        }) {
+            Ok(list) => Ok(list),
+            Err(e) => {
+                error!("list: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Attach arbitrary data to the polygon by given id, returned by `get` and `list`.
+    /// Pass `None` to remove it.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().set_metadata("my-polygon", Some(serde_json::json!({ "tooltip": "Close" })))?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.setMetadata('my-polygon', { tooltip: 'Close' });
+    /// ```
+    pub fn set_metadata(&self, id: &str, metadata: Option<serde_json::Value>) -> Result<()> {
+        trace!("set_metadata: {id} - {metadata:?}");
+        match self
+            .registry()
+            .and_then(|registry| view::set_metadata(&registry, id, metadata))
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("set_metadata: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Set the units of points, lengths and positions exchanged with the plugin,
+    /// both by methods like `update` and `hit_test`, and by the positions of `Event`.
+    ///
+    /// `CoordinateMode::WidthNormalized` is used by default, where both x and y are
+    /// a `percentage based on the screen width`, as described throughout this documentation.
+    /// Polygons already registered keep their place on the screen when the mode changes.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// use tauri_plugin_polygon::CoordinateMode;
+    /// app.polygon().set_coordinate_mode(CoordinateMode::Logical);
+    /// app.polygon().update("my-polygon", vec![(0.0, 0.0), (100.0, 0.0), (100.0, 40.0), (0.0, 40.0)])?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.setCoordinateMode('logical');
+    /// ```
+    pub fn set_coordinate_mode(&self, mode: CoordinateMode) {
+        trace!("set_coordinate_mode: {mode:?}");
+        self.state.set_coordinate_mode(mode);
+    }
+    pub fn coordinate_mode(&self) -> CoordinateMode {
+        self.state.coordinate_mode()
+    }
+    /// Enable or disable normalization of points passed to `update` and friends.
+    ///
+    /// Points are validated before being saved. By default, duplicate consecutive vertices are rejected
+    /// with `Error::DuplicateVertex`. With normalization enabled, they are removed instead,
+    /// along with explicit closing vertices (rings are always closed implicitly).
+    ///
+    /// Other checks apply either way:
+    /// 1. `Error::InvalidCoordinate` for `NaN` or infinite values.
+    /// 2. `Error::DegeneratePolygon` for shapes without area, e.g. all vertices on a line.
+    /// 3. `Error::SelfIntersecting` for rings crossing themselves, unless `FillRule::NonZero` is used.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().set_normalization(true);
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.setNormalization(true);
+    /// ```
+    pub fn set_normalization(&self, enabled: bool) {
+        trace!("set_normalization: {enabled}");
+        self.state.set_normalization(enabled);
+    }
+    /// Start writing a recording to a JSON Lines file at `path`, replacing the one in progress.
+    ///
+    /// Input events, changes of windows, monitors and polygons, and the events and click-through
+    /// changes of windows they lead to are written, of all windows. To reproduce an issue,
+    /// start recording before it happens, and replay the file with `Harness::replay` of the `test-harness` feature.
+    /// The window selected with `window` becomes the default one of the replay.
+    ///
+    /// # Errors
+    /// This function will return an error if the file can not be created.
+    /// Failures to write it later stop the recording, and are emitted as `Event::Error`,
+    /// with the next input event if writing a change of polygons or windows failed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().record("clicks-go-through.jsonl")?;
+    /// // ...reproduce the issue
+    /// app.polygon().stop_recording()?;
+    /// ```
+    pub fn record(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        trace!("record: {}", path.display());
+
+        match self.state.recorder.start(
+            path,
+            &self.state,
+            &self.window_label,
+            Arc::new(SystemClock),
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("record: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Stop the recording in progress, if any.
+    pub fn stop_recording(&self) -> Result<()> {
+        trace!("stop_recording");
+        match self.state.recorder.stop() {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("stop_recording: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Make the polygon by given id a circle.
+    /// Within the circle, mouse events will not go through.
+    ///
+    /// # Notice
+    /// Both `center` and `radius` should be converted to a `percentage based on the screen width`.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().update_circle("my-polygon", (0.5, 0.3), 0.02)?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.updateCircle('my-polygon', [0.5, 0.3], 0.02);
+    /// ```
+    pub fn update_circle(&self, id: &str, center: (f64, f64), radius: f64) -> Result<()> {
+        trace!("update_circle: {id} - {center:?}, {radius}");
+        match self.registry().and_then(|registry| {
+            let convert = self.state.convert(&registry.frame);
+            view::update_shape(
+                &registry,
+                id,
+                Shape::Circle {
+                    center: convert.point_from_viewport(center.0, center.1),
+                    radius: convert.length_from_viewport(radius),
+                },
+                self.state.normalize_points(),
+            )
+        }) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("update_circle: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Make the polygon by given id an axis-aligned ellipse.
+    /// Within the ellipse, mouse events will not go through.
+    ///
+    /// # Notice
+    /// `center` and both radii should be converted to a `percentage based on the screen width`.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().update_ellipse("my-polygon", (0.5, 0.3), 0.04, 0.02)?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.updateEllipse('my-polygon', [0.5, 0.3], 0.04, 0.02);
+    /// ```
+    pub fn update_ellipse(
+        &self,
+        id: &str,
+        center: (f64, f64),
+        radius_x: f64,
+        radius_y: f64,
+    ) -> Result<()> {
+        trace!("update_ellipse: {id} - {center:?}, {radius_x}, {radius_y}");
+        match self.registry().and_then(|registry| {
+            let convert = self.state.convert(&registry.frame);
+            view::update_shape(
+                &registry,
+                id,
+                Shape::Ellipse {
+                    center: convert.point_from_viewport(center.0, center.1),
+                    radius_x: convert.length_from_viewport(radius_x),
+                    radius_y: convert.length_from_viewport(radius_y),
+                },
+                self.state.normalize_points(),
+            )
+        }) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("update_ellipse: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Make the polygon by given id a rectangle with rounded corners.
+    /// Within the rectangle, mouse events will not go through.
+    ///
+    /// # Notice
+    /// 1. All values should be converted to a `percentage based on the screen width`.
+    /// 2. `(x, y)` is the top-left corner of the rectangle.
+    /// 3. `radius` is clamped to half of the shorter side, so a pill shape can be made with a large radius.
+    ///
+    /// # Errors
+    /// This function will return an error if the `id` provided can not be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// // backend with rust
+    /// app.polygon().update_rounded_rect("my-polygon", (0.1, 0.1), (0.2, 0.03), 0.015)?;
+    /// ```
+    /// ```javascript
+    /// // frontend with js
+    /// import { polygon } from 'tauri-plugin-polygon-api';
+    /// await polygon.updateRoundedRect('my-polygon', [0.1, 0.1], [0.2, 0.03], 0.015);
+    /// ```
+    pub fn update_rounded_rect(
+        &self,
+        id: &str,
+        position: (f64, f64),
+        size: (f64, f64),
+        radius: f64,
+    ) -> Result<()> {
+        trace!("update_rounded_rect: {id} - {position:?}, {size:?}, {radius}");
+        match self.registry().and_then(|registry| {
+            let convert = self.state.convert(&registry.frame);
+            let (x, y) = convert.point_from_viewport(position.0, position.1);
+            let (width, height) = convert.point_from_viewport(size.0, size.1);
+            view::update_shape(
+                &registry,
+                id,
+                Shape::RoundedRect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    radius: convert.length_from_viewport(radius),
+                },
+                self.state.normalize_points(),
+            )
+        }) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("update_rounded_rect: {e}");
+                self.emit(crate::Event::Error(e.clone()));
+                Err(e)
+            }
+        }
+    }
+    /// Clear all polygons of the window physically.
     ///
     /// # Example
     /// ```no_run
@@ -246,19 +1200,26 @@ impl<R: Runtime> Polygon<R> {
     /// ```
     pub fn clear(&self) -> Result<()> {
         trace!("clear");
-        match view::clear() {
+        match self.registry().and_then(|registry| view::clear(&registry)) {
             Ok(_) => Ok(()),
             Err(e) => {
                 error!("clear: {e}");
-                self.emit(&self.app_handle, crate::Event::Error(e.clone()));
+                self.emit(crate::Event::Error(e.clone()));
                 Err(e)
             }
         }
     }
+    /// Stop the thread running the callback, which drops it.
     pub(crate) fn destroy(&self) -> Result<()> {
-        let ptr = self.callback.load(Ordering::SeqCst);
-        self.callback.store(std::ptr::null_mut(), Ordering::SeqCst);
-        drop(unsafe { Box::from_raw(ptr) });
+        let _ = self.callback.send(None);
         Ok(())
     }
 }
+
+fn to_viewport(convert: &Convert, info: PolygonInfo) -> PolygonInfo {
+    PolygonInfo {
+        shape: convert.shape_to_viewport(&info.shape),
+        padding: convert.length_to_viewport(info.padding),
+        ..info
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use log::trace;
+use tauri::{Manager, Runtime, Window, WindowEvent};
+
+use crate::grab::{emit, Event};
+use crate::polygon::Bounds;
+use crate::record::Record;
+use crate::registry::Registry;
+use crate::state::State;
+use crate::view;
+
+/// Keep the conversion basis of the window and the monitor layout in sync with the window.
+pub(crate) fn watch<R: Runtime>(win: Window<R>, state: Arc<State>) {
+    let handle = win.app_handle().clone();
+    let window = win.clone();
+
+    let registry = match state.registry(win.label()) {
+        Ok(registry) => registry,
+        Err(e) => {
+            emit(&handle, Event::Error(e));
+            return;
+        }
+    };
+
+    if let Err(e) = refresh_monitors(&win, &state, &registry) {
+        emit(&handle, Event::Error(e));
+    }
+
+    win.on_window_event(move |event| {
+        let result = match event {
+            // polygons are relative to the window, so they follow it when it moves
+            WindowEvent::Moved(position) => {
+                registry
+                    .frame
+                    .set_position(position.x as f64, position.y as f64);
+                registry.record_frame();
+                refresh_monitors(&window, &state, &registry)
+            }
+            // monitors may have been plugged, unplugged or rearranged as well
+            WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
+                refresh(&window, &state, &registry)
+                    .and_then(|_| refresh_monitors(&window, &state, &registry))
+            }
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            emit(&handle, Event::Error(e));
+        }
+    });
+}
+
+/// Save the areas of all monitors, and which one hosts the window.
+fn refresh_monitors<R: Runtime>(
+    win: &Window<R>,
+    state: &State,
+    registry: &Registry,
+) -> crate::Result<()> {
+    let monitors = win
+        .available_monitors()?
+        .iter()
+        .map(|monitor| {
+            let (position, size) = (monitor.position(), monitor.size());
+            Bounds {
+                min_x: position.x as f64,
+                min_y: position.y as f64,
+                max_x: position.x as f64 + size.width as f64,
+                max_y: position.y as f64 + size.height as f64,
+            }
+        })
+        .collect::<Vec<Bounds>>();
+
+    let host = win.current_monitor()?.and_then(|current| {
+        monitors.iter().position(|bounds| {
+            (bounds.min_x, bounds.min_y)
+                == (current.position().x as f64, current.position().y as f64)
+        })
+    });
+
+    let mut current = state.monitors.write()?;
+    if *current != monitors {
+        *current = monitors;
+        drop(current);
+        state.recorder.change(|| Record::Monitors {
+            monitors: state
+                .monitors
+                .read()
+                .map(|monitors| monitors.clone())
+                .unwrap_or_default(),
+        });
+    }
+    if registry.frame.host_monitor() != host {
+        registry.frame.set_host_monitor(host);
+        registry.record_frame();
+    }
+    Ok(())
+}
+
+/// Update the size and scale factor of the window, and re-project polygons,
+/// so they keep their coordinates in the current `CoordinateMode`.
+fn refresh<R: Runtime>(win: &Window<R>, state: &State, registry: &Registry) -> crate::Result<()> {
+    let size = win.outer_size()?;
+    let scale_factor = win.scale_factor()?;
+    let (width, height) = (size.width as f64, size.height as f64);
+
+    // minimized windows report a zero size, which would collapse all polygons
+    if width <= 0.0 || height <= 0.0 {
+        return Ok(());
+    }
+    let frame = &registry.frame;
+    if width == frame.width() && height == frame.height() && scale_factor == frame.scale_factor() {
+        return Ok(());
+    }
+    trace!(
+        "display of {} changed: {width}x{height}, scale factor {scale_factor}",
+        registry.label()
+    );
+
+    let from = state.convert(frame);
+    frame.set_size(width, height, scale_factor);
+    registry.record_frame();
+    let to = state.convert(frame);
+
+    let (old_x, old_y) = from.point_from_viewport(1.0, 1.0);
+    if (old_x, old_y) != to.point_from_viewport(1.0, 1.0) && old_x > 0.0 && old_y > 0.0 {
+        view::reproject(registry, &from, &to)?;
+    }
+
+    emit(
+        win.app_handle(),
+        Event::DisplayChanged {
+            window: registry.label().to_string(),
+            width,
+            height,
+            scale_factor,
+        },
+    );
+    Ok(())
+}
@@ -1,14 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::{
-    collections::{HashMap, HashSet},
-    sync::{PoisonError, RwLockReadGuard, RwLockWriteGuard},
-};
-
-use crate::polygon;
+use std::sync::PoisonError;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(thiserror::Error, Debug, Deserialize, Serialize, Clone)]
+#[derive(thiserror::Error, Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub enum Error {
     #[error(
         "Not Initialized. Call tauri_wherever::init(app_handle) first when setup a tauri app."
@@ -20,10 +15,28 @@ pub enum Error {
     PolygonExists(String),
     #[error("At least 3 points needed but got {0}.")]
     PointsNotEnough(usize),
+    #[error("Invalid coordinate at {0}. Coordinates should be finite numbers.")]
+    InvalidCoordinate(String),
+    #[error("Duplicate consecutive vertices at {0}.")]
+    DuplicateVertex(String),
+    #[error("Polygon is degenerate. {0}")]
+    DegeneratePolygon(String),
+    #[error("Polygon is self-intersecting. {0}")]
+    SelfIntersecting(String),
+    #[error("Invalid SVG path data. {0}")]
+    InvalidSvgPath(String),
     #[error("Can not read/write cache. {0}")]
     LockError(String),
     #[error("Failed to initialize plugin. {0}")]
     PluginInitializationError(String),
+    #[error("Failed to access the window. {0}")]
+    WindowError(String),
+    #[error("Window with label [{0}] not found.")]
+    WindowNotFound(String),
+    #[error("No input mode is available. {0}")]
+    InputUnavailable(String),
+    #[error("Failed to record or replay. {0}")]
+    RecordingError(String),
 }
 
 impl From<std::io::Error> for Error {
@@ -32,20 +45,14 @@ impl From<std::io::Error> for Error {
     }
 }
 
-impl<'a> From<PoisonError<RwLockWriteGuard<'a, HashSet<String>>>> for Error {
-    fn from(error: PoisonError<RwLockWriteGuard<'a, HashSet<String>>>) -> Self {
-        Error::LockError(format!("{error}"))
-    }
-}
-
-impl<'a> From<PoisonError<RwLockReadGuard<'a, HashMap<String, polygon::Polygon>>>> for Error {
-    fn from(error: PoisonError<RwLockReadGuard<'a, HashMap<String, polygon::Polygon>>>) -> Self {
-        Error::LockError(format!("{error}"))
+impl From<tauri::Error> for Error {
+    fn from(error: tauri::Error) -> Self {
+        Error::WindowError(format!("{error}"))
     }
 }
 
-impl<'a> From<PoisonError<RwLockWriteGuard<'a, HashMap<String, polygon::Polygon>>>> for Error {
-    fn from(error: PoisonError<RwLockWriteGuard<'a, HashMap<String, polygon::Polygon>>>) -> Self {
+impl<T> From<PoisonError<T>> for Error {
+    fn from(error: PoisonError<T>) -> Self {
         Error::LockError(format!("{error}"))
     }
 }
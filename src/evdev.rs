@@ -0,0 +1,481 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use evdev_rs::enums::{EventCode, InputProp, EV_ABS, EV_KEY, EV_REL, EV_SYN};
+use evdev_rs::{Device, ReadFlag, ReadStatus};
+use log::{error, warn};
+
+use crate::error::{Error, Result};
+use crate::input::{Button, InputBackend, InputEvent, InputSink};
+use crate::polygon::Bounds;
+
+/// Reads pointer devices in `/dev/input` on Linux, which works on Wayland as well,
+/// where global cursor positions are not available to clients.
+///
+/// The cursor position is integrated from relative and absolute motion of the devices,
+/// clamped to the screen. Pointer acceleration of the compositor is not applied,
+/// so the tracked position can drift from the cursor drawn by the compositor.
+/// Reading the devices needs the user to be in the `input` group.
+///
+/// # Example
+/// ```no_run
+/// tauri::Builder::default()
+///     .plugin(
+///         tauri_plugin_polygon::Builder::new(|_app, _event| {})
+///             .input(tauri_plugin_polygon::EvdevBackend::new())
+///             .build(),
+///     );
+/// ```
+#[derive(Debug, Default)]
+pub struct EvdevBackend {
+    devices: Vec<PathBuf>,
+}
+
+impl EvdevBackend {
+    /// Read all pointer devices found in `/dev/input` when started.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Read the device at the given path, e.g. `/dev/input/event4`, call it again for more devices.
+    /// Devices are no longer looked up in `/dev/input` then.
+    pub fn device(mut self, path: impl Into<PathBuf>) -> Self {
+        self.devices.push(path.into());
+        self
+    }
+}
+
+impl InputBackend for EvdevBackend {
+    fn start(self: Box<Self>, sink: InputSink) -> Result<()> {
+        let paths = if self.devices.is_empty() {
+            fs::read_dir("/dev/input")?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with("event"))
+                })
+                .collect()
+        } else {
+            self.devices
+        };
+
+        let mut devices = Vec::new();
+        for path in paths {
+            match open(&path) {
+                Ok(device) if is_pointer(&device) => devices.push(path),
+                Ok(_) => {}
+                Err(e) => warn!("Failed to open {}: {e}", path.display()),
+            }
+        }
+        if devices.is_empty() {
+            return Err(Error::InputUnavailable(
+                "No pointer device could be read in /dev/input, which needs the `input` group."
+                    .into(),
+            ));
+        }
+
+        // all devices move the same cursor
+        let cursor = Arc::new(Mutex::new(None));
+        for path in devices {
+            let sink = sink.clone();
+            let cursor = cursor.clone();
+            thread::Builder::new()
+                .name("polygon-evdev".to_string())
+                .spawn(move || {
+                    // a `Device` can not be sent to the thread, so it is opened again
+                    if let Err(e) = open(&path).and_then(|device| read(&device, &sink, &cursor)) {
+                        if e.raw_os_error() == Some(libc::ENODEV) {
+                            warn!("Input device {} was removed.", path.display());
+                            return;
+                        }
+                        error!("Failed to read {}: {e}", path.display());
+                        sink.error(Error::InputUnavailable(format!(
+                            "Failed to read {}: {e}.",
+                            path.display()
+                        )));
+                    }
+                })?;
+        }
+        Ok(())
+    }
+}
+
+fn open(path: &Path) -> io::Result<Device> {
+    Device::new_from_fd(File::open(path)?)
+}
+
+/// Whether the device moves a cursor, i.e. mice, touchpads, touchscreens and tablets.
+fn is_pointer(device: &Device) -> bool {
+    let moves = device.has(&EventCode::EV_REL(EV_REL::REL_X))
+        || device.has(&EventCode::EV_ABS(EV_ABS::ABS_X));
+    let clicks = device.has(&EventCode::EV_KEY(EV_KEY::BTN_LEFT))
+        || device.has(&EventCode::EV_KEY(EV_KEY::BTN_TOUCH));
+    moves && clicks
+}
+
+/// Blocks until reading the device fails.
+fn read(device: &Device, sink: &InputSink, cursor: &Mutex<Option<(f64, f64)>>) -> io::Result<()> {
+    let mut tracker = Tracker::new(device);
+    let mut flags = ReadFlag::NORMAL | ReadFlag::BLOCKING;
+    loop {
+        let event = match device.next_event(flags) {
+            // events were dropped, read the changes of the device state before going on
+            Ok((ReadStatus::Sync, event)) => {
+                flags = ReadFlag::SYNC;
+                event
+            }
+            Ok((ReadStatus::Success, event)) => event,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                flags = ReadFlag::NORMAL | ReadFlag::BLOCKING;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        if tracker.feed(&event) {
+            let mut cursor = cursor.lock().map_err(|e| io::Error::other(e.to_string()))?;
+            for event in tracker.flush(&mut cursor, sink.screen()) {
+                sink.send(event);
+            }
+        }
+    }
+}
+
+/// Range of an absolute axis of a device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Axis {
+    min: i32,
+    max: i32,
+}
+
+impl Axis {
+    /// Position of a value on the axis, from `0` to `1`.
+    fn fraction(&self, value: i32) -> f64 {
+        (value - self.min) as f64 / (self.max - self.min) as f64
+    }
+}
+
+/// Integrates events of a device into the cursor position,
+/// one frame of events up to `SYN_REPORT` at a time.
+#[derive(Debug, Default)]
+struct Tracker {
+    abs_x: Option<Axis>,
+    abs_y: Option<Axis>,
+    /// Absolute axes are the position of a finger on a touchpad, which moves the cursor relatively
+    touchpad: bool,
+    /// `BTN_TOUCH` clicks, for touchscreens which have no buttons
+    touch_clicks: bool,
+    /// Relative motion of the frame
+    dx: f64,
+    dy: f64,
+    /// Absolute axis values of the frame
+    x: Option<i32>,
+    y: Option<i32>,
+    /// Last absolute axis values of the finger on a touchpad, `None` until it touches again
+    touch_x: Option<i32>,
+    touch_y: Option<i32>,
+    /// Button and wheel events of the frame, sent after the motion
+    pending: Vec<InputEvent>,
+}
+
+impl Tracker {
+    fn new(device: &Device) -> Self {
+        let axis = |code| {
+            device
+                .abs_info(&EventCode::EV_ABS(code))
+                .filter(|info| info.maximum > info.minimum)
+                .map(|info| Axis {
+                    min: info.minimum,
+                    max: info.maximum,
+                })
+        };
+        let abs_x = axis(EV_ABS::ABS_X);
+        Self {
+            abs_x,
+            abs_y: axis(EV_ABS::ABS_Y),
+            touchpad: abs_x.is_some() && device.has(&InputProp::INPUT_PROP_POINTER),
+            touch_clicks: !device.has(&EventCode::EV_KEY(EV_KEY::BTN_LEFT)),
+            ..Self::default()
+        }
+    }
+    /// Add an event to the frame, returns `true` once the frame is complete.
+    fn feed(&mut self, event: &evdev_rs::InputEvent) -> bool {
+        let pressed = event.value != 0;
+        match event.event_code {
+            EventCode::EV_SYN(EV_SYN::SYN_REPORT) => return true,
+            EventCode::EV_REL(EV_REL::REL_X) => self.dx += event.value as f64,
+            EventCode::EV_REL(EV_REL::REL_Y) => self.dy += event.value as f64,
+            EventCode::EV_REL(EV_REL::REL_WHEEL) => self.pending.push(InputEvent::Wheel {
+                delta_x: 0.0,
+                delta_y: event.value as f64,
+            }),
+            EventCode::EV_REL(EV_REL::REL_HWHEEL) => self.pending.push(InputEvent::Wheel {
+                delta_x: event.value as f64,
+                delta_y: 0.0,
+            }),
+            EventCode::EV_ABS(EV_ABS::ABS_X) => self.x = Some(event.value),
+            EventCode::EV_ABS(EV_ABS::ABS_Y) => self.y = Some(event.value),
+            EventCode::EV_KEY(EV_KEY::BTN_TOUCH) => {
+                // a new touch starts where the finger is put down
+                self.touch_x = None;
+                self.touch_y = None;
+                if self.touch_clicks {
+                    self.pending.push(button(Button::Left, pressed));
+                }
+            }
+            EventCode::EV_KEY(EV_KEY::BTN_LEFT) => self.pending.push(button(Button::Left, pressed)),
+            EventCode::EV_KEY(EV_KEY::BTN_RIGHT) => {
+                self.pending.push(button(Button::Right, pressed))
+            }
+            EventCode::EV_KEY(EV_KEY::BTN_MIDDLE) => {
+                self.pending.push(button(Button::Middle, pressed))
+            }
+            EventCode::EV_KEY(EV_KEY::BTN_SIDE | EV_KEY::BTN_EXTRA) => {
+                self.pending.push(button(Button::Other, pressed))
+            }
+            _ => {}
+        }
+        false
+    }
+    /// Apply the frame to the cursor, and take the events to send.
+    /// The cursor starts in the center of the screen, and is clamped to it.
+    fn flush(
+        &mut self,
+        cursor: &mut Option<(f64, f64)>,
+        screen: Option<Bounds>,
+    ) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        let (x, y) = cursor.unwrap_or_else(|| {
+            screen.map_or((0.0, 0.0), |s| {
+                ((s.min_x + s.max_x) / 2.0, (s.min_y + s.max_y) / 2.0)
+            })
+        });
+        let (mut new_x, mut new_y) = (x + self.dx, y + self.dy);
+
+        if let Some(screen) = screen {
+            let width = screen.max_x - screen.min_x;
+            let height = screen.max_y - screen.min_y;
+            if self.touchpad {
+                // the whole touchpad spans the screen
+                if let (Some(value), Some(axis)) = (self.x, self.abs_x) {
+                    if let Some(last) = self.touch_x {
+                        new_x += (axis.fraction(value) - axis.fraction(last)) * width;
+                    }
+                    self.touch_x = Some(value);
+                }
+                if let (Some(value), Some(axis)) = (self.y, self.abs_y) {
+                    if let Some(last) = self.touch_y {
+                        new_y += (axis.fraction(value) - axis.fraction(last)) * height;
+                    }
+                    self.touch_y = Some(value);
+                }
+            } else {
+                if let (Some(value), Some(axis)) = (self.x, self.abs_x) {
+                    new_x = screen.min_x + axis.fraction(value) * width;
+                }
+                if let (Some(value), Some(axis)) = (self.y, self.abs_y) {
+                    new_y = screen.min_y + axis.fraction(value) * height;
+                }
+            }
+            // right and bottom edges belong to no monitor
+            new_x = new_x.clamp(screen.min_x, screen.max_x - 1.0);
+            new_y = new_y.clamp(screen.min_y, screen.max_y - 1.0);
+        }
+
+        if cursor.is_none() || (new_x, new_y) != (x, y) {
+            *cursor = Some((new_x, new_y));
+            events.push(InputEvent::MouseMove { x: new_x, y: new_y });
+        }
+        events.append(&mut self.pending);
+
+        self.dx = 0.0;
+        self.dy = 0.0;
+        self.x = None;
+        self.y = None;
+        events
+    }
+}
+
+fn button(button: Button, pressed: bool) -> InputEvent {
+    if pressed {
+        InputEvent::ButtonPress { button }
+    } else {
+        InputEvent::ButtonRelease { button }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use evdev_rs::{util::int_to_event_code, TimeVal};
+
+    use super::*;
+
+    const SCREEN: Bounds = Bounds {
+        min_x: 0.0,
+        min_y: 0.0,
+        max_x: 1920.0,
+        max_y: 1080.0,
+    };
+
+    /// Feed `(type, code, value)` events, as printed by `evtest`, and collect the events to send.
+    fn replay(
+        tracker: &mut Tracker,
+        cursor: &mut Option<(f64, f64)>,
+        stream: &[(u32, u32, i32)],
+    ) -> Vec<InputEvent> {
+        let time = TimeVal::new(0, 0);
+        let mut events = Vec::new();
+        for (kind, code, value) in stream {
+            let event = evdev_rs::InputEvent::new(&time, &int_to_event_code(*kind, *code), *value);
+            if tracker.feed(&event) {
+                events.extend(tracker.flush(cursor, Some(SCREEN)));
+            }
+        }
+        events
+    }
+
+    fn moved(x: f64, y: f64) -> InputEvent {
+        InputEvent::MouseMove { x, y }
+    }
+
+    #[test]
+    fn mouse_moves_relatively_and_clicks() {
+        let mut tracker = Tracker::default();
+        let mut cursor = Some((100.0, 100.0));
+        let events = replay(
+            &mut tracker,
+            &mut cursor,
+            &[
+                // REL_X, REL_Y
+                (2, 0, 5),
+                (2, 1, -3),
+                (0, 0, 0),
+                (2, 0, 2),
+                (0, 0, 0),
+                // BTN_LEFT down and up
+                (1, 272, 1),
+                (0, 0, 0),
+                (1, 272, 0),
+                (0, 0, 0),
+                // REL_WHEEL down, BTN_RIGHT in the same frame as a motion
+                (2, 8, -1),
+                (0, 0, 0),
+                (2, 1, 1),
+                (1, 273, 1),
+                (0, 0, 0),
+            ],
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                moved(105.0, 97.0),
+                moved(107.0, 97.0),
+                button(Button::Left, true),
+                button(Button::Left, false),
+                InputEvent::Wheel {
+                    delta_x: 0.0,
+                    delta_y: -1.0
+                },
+                moved(107.0, 98.0),
+                button(Button::Right, true),
+            ]
+        );
+        assert_eq!(cursor, Some((107.0, 98.0)));
+    }
+
+    #[test]
+    fn cursor_starts_centered_and_is_clamped() {
+        let mut tracker = Tracker::default();
+        let mut cursor = None;
+        let events = replay(
+            &mut tracker,
+            &mut cursor,
+            &[(2, 0, 0), (0, 0, 0), (2, 0, -5000), (2, 1, 5000), (0, 0, 0)],
+        );
+
+        assert_eq!(events, vec![moved(960.0, 540.0), moved(0.0, 1079.0)]);
+        // pushing against the edge does not move it
+        assert!(replay(&mut tracker, &mut cursor, &[(2, 0, -10), (0, 0, 0)]).is_empty());
+    }
+
+    #[test]
+    fn absolute_device_maps_onto_screen() {
+        let axis = Some(Axis { min: 0, max: 32767 });
+        let mut tracker = Tracker {
+            abs_x: axis,
+            abs_y: axis,
+            touch_clicks: true,
+            ..Tracker::default()
+        };
+        let mut cursor = None;
+        let events = replay(
+            &mut tracker,
+            &mut cursor,
+            &[
+                // ABS_X, ABS_Y and BTN_TOUCH of a touchscreen
+                (3, 0, 32767),
+                (3, 1, 0),
+                (1, 330, 1),
+                (0, 0, 0),
+                // only ABS_Y changes
+                (3, 1, 16383),
+                (0, 0, 0),
+                (1, 330, 0),
+                (0, 0, 0),
+            ],
+        );
+
+        let y = 16383.0 / 32767.0 * 1080.0;
+        assert_eq!(
+            events,
+            vec![
+                moved(1919.0, 0.0),
+                button(Button::Left, true),
+                moved(1919.0, y),
+                button(Button::Left, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn touchpad_moves_relatively() {
+        let axis = Some(Axis { min: 0, max: 1024 });
+        let mut tracker = Tracker {
+            abs_x: axis,
+            abs_y: axis,
+            touchpad: true,
+            ..Tracker::default()
+        };
+        let mut cursor = Some((500.0, 500.0));
+        let events = replay(
+            &mut tracker,
+            &mut cursor,
+            &[
+                // the finger is put down anywhere without moving the cursor
+                (1, 330, 1),
+                (3, 0, 800),
+                (3, 1, 800),
+                (0, 0, 0),
+                (3, 0, 900),
+                (0, 0, 0),
+                // lifted, and put down elsewhere
+                (1, 330, 0),
+                (0, 0, 0),
+                (1, 330, 1),
+                (3, 0, 100),
+                (3, 1, 800),
+                (0, 0, 0),
+                (3, 1, 700),
+                (0, 0, 0),
+            ],
+        );
+
+        assert_eq!(events, vec![moved(687.5, 500.0), moved(687.5, 394.53125)]);
+    }
+}
@@ -1,42 +1,81 @@
 use std::{
     fmt::Display,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        RwLock,
-    },
-    thread,
-    time::Instant,
+    sync::{atomic::Ordering, Arc, RwLock},
+    time::{Duration, Instant},
 };
 
-use log::{ error, trace };
+use log::{error, trace};
 use portable_atomic::AtomicF64;
-use rdev;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tauri::{AppHandle, Emitter, Manager, Runtime, Window};
 
-use crate::statics::REGISTERED_POLYGON;
-use crate::utils::Convert;
+use crate::input::{Button, InputEvent, InputSink};
+use crate::record::Record;
+use crate::registry::Registry;
+use crate::state::State;
 use crate::view;
 use crate::PolygonExt;
 
-/// Saves physical pixel number
-static MOUSE_X: AtomicF64 = AtomicF64::new(0.0);
-/// Saves physical pixel number
-static MOUSE_Y: AtomicF64 = AtomicF64::new(0.0);
-
-static MOUSE_IN_POLYGON: AtomicBool = AtomicBool::new(false);
-
-#[derive(Debug)]
+/// `topmost` is the id of the polygon on top at the cursor position, see `Polygon::set_z_index`.
+/// Clicks are emitted in and out of polygons, drags only out of them.
+/// `monitor` is the index of the monitor the cursor is on, in the order of `Window::available_monitors`.
+/// `window` is the label of the window the polygons belong to, for clicks the one of `topmost`.
+/// Positions are relative to the default window, see `Polygon::window_label`.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Event {
-    LeftClick { x: f64, y: f64 },
-    DoubleClick { x: f64, y: f64 },
-    RightClick { x: f64, y: f64 },
-    Drag { from: Position, to: Position },
-    MouseMove { x: f64, y: f64 },
-    MouseEnter(Vec<String>),
-    MouseLeave,
-    Wheel { x: f64, y: f64 },
+    LeftClick {
+        x: f64,
+        y: f64,
+        topmost: Option<String>,
+        window: Option<String>,
+        monitor: Option<usize>,
+    },
+    DoubleClick {
+        x: f64,
+        y: f64,
+        topmost: Option<String>,
+        window: Option<String>,
+        monitor: Option<usize>,
+    },
+    RightClick {
+        x: f64,
+        y: f64,
+        topmost: Option<String>,
+        window: Option<String>,
+        monitor: Option<usize>,
+    },
+    Drag {
+        from: Position,
+        to: Position,
+    },
+    MouseMove {
+        x: f64,
+        y: f64,
+        monitor: Option<usize>,
+    },
+    /// Emitted when the cursor enters a polygon, or another polygon becomes the topmost.
+    /// `ids` are all polygons the cursor is in, the topmost first.
+    MouseEnter {
+        window: String,
+        ids: Vec<String>,
+        topmost: String,
+    },
+    MouseLeave {
+        window: String,
+    },
+    Wheel {
+        x: f64,
+        y: f64,
+    },
+    /// Emitted when the size or scale factor of the window changes.
+    /// Polygons are re-projected, so they keep their coordinates in the current `CoordinateMode`.
+    DisplayChanged {
+        window: String,
+        width: f64,
+        height: f64,
+        scale_factor: f64,
+    },
     Error(crate::Error),
 }
 
@@ -51,12 +90,13 @@ impl Display for Event {
             Event::MouseLeave { .. } => write!(f, "POLYGON_MOUSE_LEAVE"),
             Event::Wheel { .. } => write!(f, "POLYGON_WHEEL"),
             Event::Drag { .. } => write!(f, "POLYGON_DRAG"),
+            Event::DisplayChanged { .. } => write!(f, "POLYGON_DISPLAY_CHANGED"),
             Event::Error(..) => write!(f, "POLYGON_ERROR"),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
@@ -68,270 +108,432 @@ impl Display for Position {
     }
 }
 
-fn get_mouse_position() -> (f64, f64) {
-    Convert::to_viewport(
-        MOUSE_X.load(Ordering::SeqCst),
-        MOUSE_Y.load(Ordering::SeqCst),
-    )
+/// Position of the cursor relative to the default window, in the current `CoordinateMode`.
+fn get_mouse_position(state: &State, window_label: &str) -> (f64, f64) {
+    let (x, y) = state.mouse.position();
+    match state.registry(window_label) {
+        Ok(registry) => {
+            let (x, y) = registry.frame.screen_to_window(x, y);
+            state.convert(&registry.frame).to_viewport(x, y)
+        }
+        Err(_) => (x, y),
+    }
 }
 
-fn set_mouse_position(x: f64, y: f64) {
-    MOUSE_X.store(x, Ordering::SeqCst);
-    MOUSE_Y.store(y, Ordering::SeqCst);
+impl Event {
+    /// Payload emitted to the frontend along with the event name.
+    pub(crate) fn payload(&self) -> serde_json::Value {
+        match self {
+            Event::LeftClick {
+                x,
+                y,
+                topmost,
+                window,
+                monitor,
+            }
+            | Event::RightClick {
+                x,
+                y,
+                topmost,
+                window,
+                monitor,
+            }
+            | Event::DoubleClick {
+                x,
+                y,
+                topmost,
+                window,
+                monitor,
+            } => json!({
+                "position": {
+                    "x": x,
+                    "y": y,
+                },
+                "topmost": topmost,
+                "window": window,
+                "monitor": monitor,
+            }),
+            Event::MouseMove { x, y, monitor } => json!({
+                "position": {
+                    "x": x,
+                    "y": y,
+                },
+                "monitor": monitor,
+            }),
+            Event::Wheel { x, y } => json!({
+                "delta": {
+                    "x": x,
+                    "y": y,
+                }
+            }),
+            Event::MouseEnter {
+                window,
+                ids,
+                topmost,
+            } => json!({
+                "window": window,
+                "ids": ids,
+                "topmost": topmost,
+            }),
+            Event::MouseLeave { window } => json!({ "window": window }),
+            Event::Drag { from, to } => json!({
+                "from": {
+                    "x": from.x,
+                    "y": from.y
+                },
+                "to": {
+                    "x": to.x,
+                    "y": to.y
+                }
+            }),
+            Event::DisplayChanged {
+                window,
+                width,
+                height,
+                scale_factor,
+            } => json!({
+                "window": window,
+                "width": width,
+                "height": height,
+                "scaleFactor": scale_factor,
+            }),
+            Event::Error(e) => json!({
+                "error": e.to_string()
+            }),
+        }
+    }
 }
 
-fn emit<R: Runtime>(handle: &AppHandle<R>, event: Event) {
+pub(crate) fn emit<R: Runtime>(handle: &AppHandle<R>, event: Event) {
     match event {
-        Event::LeftClick { x, y } | Event::RightClick { x, y } | Event::DoubleClick { x, y } => {
-            trace!("emit event: {event:?}");
-            let _ = handle.emit(
-                &event.to_string(),
-                json!({
-                    "position": {
-                        "x": x,
-                        "y": y,
-                    }
-                }),
-            );
-            handle.polygon().emit(handle, event);
-        }
-        Event::MouseMove { x, y } => {
-            let _ = handle.emit(
-                &event.to_string(),
-                json!({
-                    "position": {
-                        "x": x,
-                        "y": y,
-                    }
-                }),
-            );
-            handle.polygon().emit(handle, event);
+        // too frequent to trace
+        Event::MouseMove { .. } => {}
+        Event::Error(ref e) => error!("emit event: {event}, error: {e}"),
+        _ => trace!("emit event: {event:?}"),
+    }
+    let _ = handle.emit(&event.to_string(), event.payload());
+    handle.polygon().emit(event);
+}
+
+/// Label of the window and id of the topmost polygon at the cursor position.
+/// The window the cursor is in a polygon of comes first, the others by label.
+fn topmost_at_cursor(state: &State) -> crate::Result<Option<(String, String)>> {
+    let (x, y) = state.mouse.position();
+    let monitor = state.mouse.monitor();
+    let mut registries = state.registries()?;
+    registries.sort_by_key(|registry| !registry.mouse_in_polygon.load(Ordering::SeqCst));
+    for registry in registries {
+        // polygons only live on the monitor hosting the window
+        if !registry.frame.is_on_host_monitor(monitor) {
+            continue;
         }
-        Event::Wheel { x, y } => {
-            trace!("emit event: {event:?}");
-            let _ = handle.emit(
-                &event.to_string(),
-                json!({
-                    "delta": {
-                        "x": x,
-                        "y": y,
-                    }
-                }),
-            );
-            handle.polygon().emit(handle, event);
+        let (x, y) = registry.frame.screen_to_window(x, y);
+        if let Some(id) = view::hit_test(&registry, x, y)?.into_iter().next() {
+            return Ok(Some((registry.label().to_string(), id)));
         }
-        Event::MouseEnter(ids) => {
-            trace!("emit event: MouseEnter {ids:?}");
-            let event = Event::MouseEnter(ids);
-            let _ = handle.emit(&event.to_string(), json!({}));
-            handle.polygon().emit(handle, event);
+    }
+    Ok(None)
+}
+
+/// Hit test the cursor against polygons of the window,
+/// and let cursor events through to the window only while the cursor is in one of them.
+fn cursor_moved(
+    out: &dyn Output,
+    registry: &Registry,
+    x: f64,
+    y: f64,
+    monitor: Option<usize>,
+) -> crate::Result<()> {
+    // rdev reports global positions, while polygons are relative to the window
+    let (x, y) = registry.frame.screen_to_window(x, y);
+
+    // polygons only live on the monitor hosting the window
+    let ids = if registry.frame.is_on_host_monitor(monitor) {
+        view::cursor_moved(registry, x, y)?
+    } else {
+        view::cursor_left(registry)?;
+        Vec::new()
+    };
+
+    let topmost = ids.first().cloned();
+    // not held while emitting, the callback may lock it through `Polygon` methods
+    let last_topmost = std::mem::replace(&mut *registry.topmost.write()?, topmost.clone());
+    let window = registry.label().to_string();
+
+    // we have no way to ignore cursor event separately for each polygon
+    // so we should not ignore it if there is at least one polygon in the registered area
+    if let Some(top) = topmost {
+        if !registry.mouse_in_polygon.load(Ordering::SeqCst) {
+            out.set_ignore_cursor_events(registry.label(), false)?;
+            registry.mouse_in_polygon.store(true, Ordering::SeqCst);
         }
-        Event::MouseLeave => {
-            trace!("emit event: {event:?}");
-            let _ = handle.emit(&event.to_string(), json!({}));
-            handle.polygon().emit(handle, event);
+        if last_topmost.as_ref() != Some(&top) {
+            out.emit(Event::MouseEnter {
+                window,
+                ids,
+                topmost: top,
+            });
         }
-        Event::Drag { from, to } => {
-            trace!("emit event: {event:?}");
-            let _ = handle.emit(
-                &event.to_string(),
-                json!({
-                    "from": {
-                        "x": from.x,
-                        "y": from.y
-                    },
-                    "to": {
-                        "x": to.x,
-                        "y": to.y
-                    }
-                }),
-            );
-            handle.polygon().emit(handle, event);
+    } else if registry.mouse_in_polygon.load(Ordering::SeqCst) {
+        out.set_ignore_cursor_events(registry.label(), true)?;
+        registry.mouse_in_polygon.store(false, Ordering::SeqCst);
+        out.emit(Event::MouseLeave { window });
+    }
+    Ok(())
+}
+
+/// Where gestures end up, the app at runtime and a recording in the test harness.
+pub(crate) trait Output: Send + Sync {
+    /// Label of the default window, positions of events are relative to it
+    fn window_label(&self) -> &str;
+    fn emit(&self, event: Event);
+    /// Let cursor events of the window through to the windows below it, or not
+    fn set_ignore_cursor_events(&self, window: &str, ignore: bool) -> crate::Result<()>;
+}
+
+/// Emits to the app, and writes what it does to the recording if there is one.
+struct AppOutput<R: Runtime> {
+    handle: AppHandle<R>,
+    state: Arc<State>,
+}
+
+impl<R: Runtime> Output for AppOutput<R> {
+    fn window_label(&self) -> &str {
+        self.handle.polygon().window_label()
+    }
+    fn emit(&self, event: Event) {
+        if let Err(e) = self.state.recorder.write(|| Record::emit(&event)) {
+            emit(&self.handle, Event::Error(e));
         }
-        Event::Error(e) => {
-            let err = e.clone();
-            let evt = Event::Error(e);
-
-            error!("emit event: {evt}, error: {err}",);
-            let _ = handle.emit(
-                &evt.to_string(),
-                json!({
-                    "error": err.to_string()
-                }),
-            );
-            handle.polygon().emit(handle, evt);
+        emit(&self.handle, event);
+    }
+    /// Only what the window actually did is recorded, and failing to record it does not undo it.
+    fn set_ignore_cursor_events(&self, window: &str, ignore: bool) -> crate::Result<()> {
+        self.handle
+            .get_webview_window(window)
+            .ok_or_else(|| crate::Error::WindowNotFound(window.to_string()))?
+            .set_ignore_cursor_events(ignore)?;
+        if let Err(e) = self.state.recorder.write(|| Record::IgnoreCursorEvents {
+            window: window.to_string(),
+            ignore,
+        }) {
+            emit(&self.handle, Event::Error(e));
         }
+        Ok(())
     }
 }
 
-pub fn init<R: Runtime>(win: Window<R>) {
-    let last_click_time = RwLock::new(Instant::now());
-    let last_click_pos_x = AtomicF64::new(0.0);
-    let last_click_pos_y = AtomicF64::new(0.0);
-    let press_time = RwLock::new(Instant::now());
-    let press_pos = RwLock::new(Position { x: 0.0, y: 0.0 });
-    let win_clone_01 = win.clone();
-    let win_clone_02 = win.clone();
-
-    let thread_handle = thread::Builder::new()
-        .name("polygon-grab".to_string())
-        .spawn(move || {
-            let result = rdev::grab(move |ev| match ev.event_type {
-                rdev::EventType::ButtonPress(rdev::Button::Left) => {
-                    let mut press_time = press_time.write().unwrap();
-                    *press_time = Instant::now();
-
-                    let mut press_pos = press_pos.write().unwrap();
-                    let (x, y) = get_mouse_position();
-                    press_pos.x = x;
-                    press_pos.y = y;
-
-                    Some(ev)
-                }
-                rdev::EventType::ButtonRelease(rdev::Button::Left) => {
-                    let elapsed = press_time.read().unwrap().elapsed().as_millis();
-                    let handle = win.app_handle();
-
-                    let polygons = match view::cursor_in() {
-                        Ok(v) => v,
-                        Err(e) => {
-                            emit(&handle, Event::Error(e));
-                            return Some(ev);
-                        }
-                    };
-                    // if click/drag triggered in a registered area, handle it by frontend self
-                    // otherwise, send it to frontend.
-                    if polygons.len() == 0 {
-                        let (x, y) = get_mouse_position();
-                        let press_pos = press_pos.read().unwrap();
-
-                        let last_click_x = last_click_pos_x.load(Ordering::SeqCst);
-                        let last_click_y = last_click_pos_y.load(Ordering::SeqCst);
-
-                        last_click_pos_x.store(x, Ordering::SeqCst);
-                        last_click_pos_y.store(y, Ordering::SeqCst);
-
-                        let mut last_click_time = last_click_time.write().unwrap();
-                        let last_click_elapsed = last_click_time.elapsed().as_millis();
-                        *last_click_time = Instant::now();
-
-                        // we assume it's a double click if
-                        // the elapsed is less than 150ms
-                        // the mouse position (compared to last click) has not changed
-                        // the elapsed between last click and current click is less than 400ms
-                        if elapsed < 150 && (x == last_click_x && y == last_click_y) && last_click_elapsed <= 400 {
-                            emit(&handle, Event::DoubleClick { x, y });
-                            return Some(ev);
-                        }
+/// Source of time for the click heuristics, scripted in the test harness.
+pub(crate) trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
 
-                        // we assume it's a drag if
-                        // the elapsed is more than 150ms
-                        // the mouse position (compared to press position) has changed
-                        if press_pos.x != x || press_pos.y != y {
-                            let (x, y) = get_mouse_position();
-                            emit(
-                                &handle,
-                                Event::Drag {
-                                    from: press_pos.clone(),
-                                    to: Position { x, y },
-                                },
-                            );
-                            return Some(ev);
-                        }
+/// `Instant::now`
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Turns input events into clicks, drags and cursor events of polygons.
+pub(crate) struct Gestures {
+    state: Arc<State>,
+    clock: Arc<dyn Clock>,
+    last_click_time: RwLock<Instant>,
+    last_click_pos_x: AtomicF64,
+    last_click_pos_y: AtomicF64,
+    press_time: RwLock<Instant>,
+    press_pos: RwLock<Position>,
+}
 
-                        // we assume it's a click if
-                        // the elapsed between press and release is less than 150ms
-                        // the elapsed between last click and current click is more than 400ms
-                        // trigger CleftClick by default
-                        emit(&handle, Event::LeftClick { x, y });
+impl Gestures {
+    pub(crate) fn new(state: Arc<State>, clock: Arc<dyn Clock>) -> Self {
+        let now = clock.now();
+        Self {
+            state,
+            clock,
+            last_click_time: RwLock::new(now),
+            last_click_pos_x: AtomicF64::new(0.0),
+            last_click_pos_y: AtomicF64::new(0.0),
+            press_time: RwLock::new(now),
+            press_pos: RwLock::new(Position { x: 0.0, y: 0.0 }),
+        }
+    }
+
+    /// Time passed since `instant`, as told by the clock.
+    fn elapsed(&self, instant: Instant) -> Duration {
+        self.clock.now().saturating_duration_since(instant)
+    }
+
+    pub(crate) fn handle(&self, event: crate::Result<InputEvent>, out: &dyn Output) {
+        let state = &self.state;
+        match event {
+            Ok(InputEvent::ButtonPress {
+                button: Button::Left,
+            }) => {
+                let mut press_time = self.press_time.write().unwrap();
+                *press_time = self.clock.now();
+
+                let mut press_pos = self.press_pos.write().unwrap();
+                let (x, y) = get_mouse_position(state, out.window_label());
+                press_pos.x = x;
+                press_pos.y = y;
+            }
+            Ok(InputEvent::ButtonRelease {
+                button: Button::Left,
+            }) => {
+                let elapsed = self.elapsed(*self.press_time.read().unwrap()).as_millis();
+
+                let (window, topmost) = match topmost_at_cursor(state) {
+                    Ok(hit) => hit.unzip(),
+                    Err(e) => {
+                        out.emit(Event::Error(e));
+                        return;
                     }
-                    Some(ev)
+                };
+                let (x, y) = get_mouse_position(state, out.window_label());
+                let press_pos = self.press_pos.read().unwrap();
+
+                let last_click_x = self.last_click_pos_x.load(Ordering::SeqCst);
+                let last_click_y = self.last_click_pos_y.load(Ordering::SeqCst);
+
+                self.last_click_pos_x.store(x, Ordering::SeqCst);
+                self.last_click_pos_y.store(y, Ordering::SeqCst);
+
+                let mut last_click_time = self.last_click_time.write().unwrap();
+                let last_click_elapsed = self.elapsed(*last_click_time).as_millis();
+                *last_click_time = self.clock.now();
+
+                // we assume it's a double click if
+                // the elapsed is less than 150ms
+                // the mouse position (compared to last click) has not changed
+                // the elapsed between last click and current click is less than 400ms
+                if elapsed < 150
+                    && (x == last_click_x && y == last_click_y)
+                    && last_click_elapsed <= 400
+                {
+                    out.emit(Event::DoubleClick {
+                        x,
+                        y,
+                        topmost,
+                        window,
+                        monitor: state.mouse.monitor(),
+                    });
+                    return;
                 }
-                rdev::EventType::ButtonRelease(rdev::Button::Right) => {
-                    let polygons = match view::cursor_in() {
-                        Ok(v) => v,
-                        Err(e) => {
-                            let handle = win.app_handle();
-                            emit(&handle, Event::Error(e));
-                            return Some(ev);
-                        }
-                    };
-                    // if click/drag triggered in a registered area, handle it by frontend self
-                    if polygons.len() == 0 {
-                        let (x, y) = get_mouse_position();
-                        let handle = win.app_handle();
-                        emit(&handle, Event::RightClick { x, y });
+
+                // we assume it's a drag if
+                // the elapsed is more than 150ms
+                // the mouse position (compared to press position) has changed
+                if press_pos.x != x || press_pos.y != y {
+                    // if drag triggered in a registered area, handle it by frontend self
+                    if topmost.is_none() {
+                        out.emit(Event::Drag {
+                            from: *press_pos,
+                            to: Position { x, y },
+                        });
                     }
-                    Some(ev)
+                    return;
                 }
-                rdev::EventType::MouseMove { x, y } => {
-                    set_mouse_position(x, y);
-
-                    let registered = REGISTERED_POLYGON.get().unwrap().read().unwrap();
-
-                    let mut ids = Vec::new();
-                    for polygon in registered.values() {
-                        if view::pos_contained(polygon, x, y) {
-                            polygon.set_cursor_in(true);
-                            ids.push(polygon.id().to_owned());
-                        } else {
-                            polygon.set_cursor_in(false);
-                        }
-                    }
 
-                    let handle = win.app_handle();
-
-                    // we have no way to ignore cursor event separately for each polygon
-                    // so we should not ignore it if there is at least one polygon in the registered area
-                    if ids.len() > 0 && !MOUSE_IN_POLYGON.load(Ordering::SeqCst) {
-                        win.set_ignore_cursor_events(false).unwrap();
-                        MOUSE_IN_POLYGON.store(true, Ordering::SeqCst);
-                        emit(handle, Event::MouseEnter(ids));
-                    } else if (ids.len() == 0) && MOUSE_IN_POLYGON.load(Ordering::SeqCst) {
-                        win.set_ignore_cursor_events(true).unwrap();
-                        MOUSE_IN_POLYGON.store(false, Ordering::SeqCst);
-                        emit(handle, Event::MouseLeave);
+                // we assume it's a click if
+                // the elapsed between press and release is less than 150ms
+                // the elapsed between last click and current click is more than 400ms
+                // trigger CleftClick by default
+                out.emit(Event::LeftClick {
+                    x,
+                    y,
+                    topmost,
+                    window,
+                    monitor: state.mouse.monitor(),
+                });
+            }
+            Ok(InputEvent::ButtonRelease {
+                button: Button::Right,
+            }) => {
+                let (window, topmost) = match topmost_at_cursor(state) {
+                    Ok(hit) => hit.unzip(),
+                    Err(e) => {
+                        out.emit(Event::Error(e));
+                        return;
                     }
+                };
+                let (x, y) = get_mouse_position(state, out.window_label());
+                out.emit(Event::RightClick {
+                    x,
+                    y,
+                    topmost,
+                    window,
+                    monitor: state.mouse.monitor(),
+                });
+            }
+            Ok(InputEvent::MouseMove { x, y }) => {
+                let monitor = state.monitor_at(x, y);
+                state.mouse.set_monitor(monitor);
+                state.mouse.set_position(x, y);
 
-                    let mouse_pos = get_mouse_position();
-                    emit(
-                        &handle,
-                        Event::MouseMove {
-                            x: mouse_pos.0,
-                            y: mouse_pos.1,
-                        },
-                    );
-                    Some(ev)
-                }
-                rdev::EventType::Wheel { delta_x, delta_y } => {
-                    let handle = win.app_handle();
-                    emit(
-                        &handle,
-                        Event::Wheel {
-                            x: delta_x as f64,
-                            y: delta_y as f64,
-                        },
-                    );
-                    Some(ev)
+                match state.registries() {
+                    Ok(registries) => {
+                        for registry in registries {
+                            if let Err(e) = cursor_moved(out, &registry, x, y, monitor) {
+                                out.emit(Event::Error(e));
+                            }
+                        }
+                    }
+                    Err(e) => out.emit(Event::Error(e)),
                 }
-                _ => Some(ev),
-            });
 
-            if let Err(e) = result {
-                error!("Failed to grab events: {e:?}");
-                emit(
-                    win_clone_02.app_handle(),
-                    Event::Error(crate::Error::PluginInitializationError(format!("{e:?}"))),
-                );
+                let mouse_pos = get_mouse_position(state, out.window_label());
+                out.emit(Event::MouseMove {
+                    x: mouse_pos.0,
+                    y: mouse_pos.1,
+                    monitor,
+                });
             }
-        });
-
-    if let Err(e) = thread_handle {
-        error!("Failed to start a event grab thread: {e:?}");
-        let app_handle = win_clone_01.app_handle();
-        emit(
-            app_handle,
-            Event::Error(crate::Error::PluginInitializationError(e.to_string())),
-        );
+            Ok(InputEvent::Wheel { delta_x, delta_y }) => {
+                out.emit(Event::Wheel {
+                    x: delta_x,
+                    y: delta_y,
+                });
+            }
+            Ok(_) => {}
+            Err(e) => out.emit(Event::Error(e)),
+        }
+    }
+}
+
+/// Start the input backend, once for all windows.
+pub fn init<R: Runtime>(win: Window<R>, state: Arc<State>) {
+    let backend = match state.backend.lock() {
+        Ok(mut backend) => backend.take(),
+        Err(e) => {
+            emit(win.app_handle(), Event::Error(e.into()));
+            return;
+        }
+    };
+    let Some(backend) = backend else {
+        return;
+    };
+
+    let handle = win.app_handle().clone();
+    let gestures = Gestures::new(state.clone(), Arc::new(SystemClock));
+    let output = AppOutput {
+        handle: handle.clone(),
+        state: state.clone(),
+    };
+    let sink = InputSink::new(state.clone(), move |event| {
+        if let Ok(event) = &event {
+            if let Err(e) = state.recorder.input(event) {
+                emit(&output.handle, Event::Error(e));
+            }
+        }
+        gestures.handle(event, &output);
+    });
+
+    if let Err(e) = backend.start(sink) {
+        error!("Failed to start the input backend: {e}");
+        emit(&handle, Event::Error(e));
     }
 }
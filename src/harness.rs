@@ -0,0 +1,852 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    sync::{atomic::Ordering, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::error::{Error, Result};
+use crate::grab::{Clock, Event, Gestures, Output};
+use crate::input::{Button, InputBackend, InputEvent, InputSink};
+use crate::polygon::{Bounds, Geometry, Polygon, Shape};
+use crate::record::Record;
+use crate::state::State;
+use crate::utils::CoordinateMode;
+use crate::view;
+
+/// Runs the click, drag and cursor heuristics of the plugin on a scripted input stream,
+/// without an app, windows or input devices. Enabled by the `test-harness` feature.
+///
+/// The default window `"main"` covers the only monitor of 1920x1080 physical pixels.
+/// Points of polygons are relative to their window, positions of input events to the screen,
+/// and positions of emitted events to the default window. All in physical pixels.
+///
+/// # Example
+/// ```
+/// use tauri_plugin_polygon::{Effect, Event, Harness, Step};
+///
+/// let harness = Harness::new();
+/// harness.polygon("main", "toolbar", &[(0.0, 0.0), (100.0, 0.0), (100.0, 40.0)]).unwrap();
+/// harness.play([Step::move_to(50.0, 10.0)]);
+///
+/// assert_eq!(
+///     harness.take()[..2],
+///     [
+///         Effect::IgnoreCursorEvents { window: "main".into(), ignore: false },
+///         Effect::Event(Event::MouseEnter {
+///             window: "main".into(),
+///             ids: vec!["toolbar".into()],
+///             topmost: "toolbar".into(),
+///         }),
+///     ]
+/// );
+/// ```
+pub struct Harness {
+    state: Arc<State>,
+    clock: Arc<ScriptedClock>,
+    gestures: Gestures,
+    collector: Collector,
+}
+
+/// A step of the input stream played by `Harness::play`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    /// Feed an event, as an `InputBackend` does
+    Input(InputEvent),
+    /// Let time pass
+    Wait(Duration),
+}
+
+impl Step {
+    /// Move the cursor to a position on the screen.
+    pub fn move_to(x: f64, y: f64) -> Self {
+        Step::Input(InputEvent::MouseMove { x, y })
+    }
+    pub fn press(button: Button) -> Self {
+        Step::Input(InputEvent::ButtonPress { button })
+    }
+    pub fn release(button: Button) -> Self {
+        Step::Input(InputEvent::ButtonRelease { button })
+    }
+    pub fn wait(millis: u64) -> Self {
+        Step::Wait(Duration::from_millis(millis))
+    }
+}
+
+/// What the heuristics did, in order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Effect {
+    /// The event was emitted to the app
+    Event(Event),
+    /// The window was told to let cursor events through (`ignore`) or to receive them
+    IgnoreCursorEvents { window: String, ignore: bool },
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        let harness = Self::empty("main");
+        if let Ok(mut monitors) = harness.state.monitors.write() {
+            monitors.push(Bounds {
+                min_x: 0.0,
+                min_y: 0.0,
+                max_x: 1920.0,
+                max_y: 1080.0,
+            });
+        }
+        harness.window("main", (0.0, 0.0), (1920.0, 1080.0))
+    }
+    /// Without windows and monitors.
+    fn empty(default_window: &str) -> Self {
+        let state = Arc::new(State::new(Box::new(Scripted)));
+        state.set_coordinate_mode(CoordinateMode::Physical);
+
+        let clock = Arc::new(ScriptedClock::default());
+        Self {
+            gestures: Gestures::new(state.clone(), clock.clone()),
+            collector: Collector {
+                default_window: default_window.to_string(),
+                effects: Mutex::new(Vec::new()),
+                state: state.clone(),
+            },
+            state,
+            clock,
+        }
+    }
+    /// Add a window polygons can belong to, at a position on the screen, both in physical pixels.
+    /// Adding the default window `"main"` again moves it.
+    pub fn window(self, label: &str, position: (f64, f64), size: (f64, f64)) -> Self {
+        if let Ok(registry) = self.state.insert(label) {
+            registry.frame.set_position(position.0, position.1);
+            registry.frame.set_size(size.0, size.1, 1.0);
+            registry.record_frame();
+        }
+        self
+    }
+    /// Register and show a polygon in the window, or update it if it exists.
+    pub fn polygon(&self, window: &str, id: &str, points: &[(f64, f64)]) -> Result<()> {
+        let registry = self.state.registry(window)?;
+        if !view::exists(&registry, id)? {
+            view::register(&registry, id.to_string())?;
+        }
+        view::update_shape(
+            &registry,
+            id,
+            Shape::Polygon {
+                rings: vec![points.to_vec()],
+                holes: Vec::new(),
+            },
+            false,
+        )?;
+        view::show(&registry, id)
+    }
+    /// Remove a polygon from the window.
+    pub fn remove(&self, window: &str, id: &str) -> Result<()> {
+        let registry = self.state.registry(window)?;
+        view::remove(&registry, id)
+    }
+    /// Feed the steps to the heuristics, one after another.
+    pub fn play(&self, steps: impl IntoIterator<Item = Step>) {
+        for step in steps {
+            match step {
+                Step::Input(event) => self.send(event),
+                Step::Wait(duration) => self.clock.advance(duration),
+            }
+        }
+    }
+    /// Feed an event to the heuristics, as an `InputBackend` does.
+    pub fn send(&self, event: InputEvent) {
+        if let Err(e) = self.state.recorder.input(&event) {
+            self.collector.push(Effect::Event(Event::Error(e)));
+        }
+        self.gestures.handle(Ok(event), &self.collector);
+    }
+    /// Record what the harness does like `Polygon::record`, with the time of the harness.
+    pub fn record(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.state.recorder.start(
+            path.as_ref(),
+            &self.state,
+            &self.collector.default_window,
+            self.clock.clone(),
+        )
+    }
+    pub fn stop_recording(&self) -> Result<()> {
+        self.state.recorder.stop()
+    }
+    /// Take what happened since the last call.
+    pub fn take(&self) -> Vec<Effect> {
+        self.collector
+            .effects
+            .lock()
+            .map(|mut effects| effects.drain(..).collect())
+            .unwrap_or_default()
+    }
+    /// Play a recording of `Polygon::record`, and compare what the heuristics do
+    /// with what the recorded app did after each input event.
+    ///
+    /// # Errors
+    /// Returns an error if the file can not be read, or is not a recording.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let divergences = tauri_plugin_polygon::Harness::replay("clicks-go-through.jsonl").unwrap();
+    /// assert!(divergences.is_empty(), "{divergences:#?}");
+    /// ```
+    pub fn replay(path: impl AsRef<Path>) -> Result<Vec<Divergence>> {
+        let path = path.as_ref();
+        let invalid = |line: usize, message: String| {
+            Error::RecordingError(format!("{}:{line}: {message}", path.display()))
+        };
+        let file = File::open(path).map_err(|e| invalid(0, e.to_string()))?;
+
+        let mut harness: Option<Harness> = None;
+        let mut divergences = Vec::new();
+        // the last input event, with what the recorded app did after it
+        let mut last: Option<Divergence> = None;
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| invalid(i + 1, e.to_string()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Record =
+                serde_json::from_str(&line).map_err(|e| invalid(i + 1, e.to_string()))?;
+
+            let Some(current) = harness.as_ref() else {
+                let Record::Start {
+                    default_window,
+                    mouse,
+                    monitor,
+                } = record
+                else {
+                    return Err(invalid(i + 1, "Expected a `start` record.".into()));
+                };
+                let started = Self::empty(&default_window);
+                started.state.mouse.set_position(mouse.0, mouse.1);
+                started.state.mouse.set_monitor(monitor);
+                harness = Some(started);
+                continue;
+            };
+
+            match record {
+                Record::Emit { .. } | Record::IgnoreCursorEvents { .. } => {
+                    if let Some(last) = last.as_mut() {
+                        last.expected.push(record);
+                    }
+                }
+                Record::Input { time, event } => {
+                    divergences.extend(last.take().filter(Divergence::diverged));
+                    current.clock.set(Duration::from_micros(time));
+                    current.send(event);
+                    last = Some(Divergence {
+                        line: i + 1,
+                        input: event,
+                        expected: Vec::new(),
+                        actual: current.take().into_iter().map(Record::from).collect(),
+                    });
+                }
+                record => {
+                    divergences.extend(last.take().filter(Divergence::diverged));
+                    current
+                        .apply(record)
+                        .map_err(|e| invalid(i + 1, e.to_string()))?;
+                }
+            }
+        }
+        divergences.extend(last.filter(Divergence::diverged));
+        Ok(divergences)
+    }
+    /// Restore the state of a record.
+    fn apply(&self, record: Record) -> Result<()> {
+        match record {
+            Record::CoordinateMode { mode } => self.state.set_coordinate_mode(mode),
+            Record::Monitors { monitors } => *self.state.monitors.write()? = monitors,
+            Record::Window {
+                label,
+                x,
+                y,
+                width,
+                height,
+                scale_factor,
+                host_monitor,
+            } => {
+                let registry = self.state.insert(&label)?;
+                registry.frame.set_position(x, y);
+                registry.frame.set_size(width, height, scale_factor);
+                registry.frame.set_host_monitor(host_monitor);
+            }
+            Record::Hover {
+                window,
+                ids,
+                topmost,
+                mouse_in_polygon,
+            } => {
+                let registry = self.state.registry(&window)?;
+                let polygons = registry.polygons.read()?;
+                let mut hovered = registry.hovered.write()?;
+                for id in ids {
+                    if let Some(polygon) = polygons.get(&id) {
+                        polygon.set_cursor_in(true);
+                    }
+                    hovered.insert(id);
+                }
+                *registry.topmost.write()? = topmost;
+                registry
+                    .mouse_in_polygon
+                    .store(mouse_in_polygon, Ordering::SeqCst);
+            }
+            Record::Polygon {
+                window,
+                id,
+                polygon: None,
+            } => {
+                let registry = self.state.registry(&window)?;
+                view::remove(&registry, &id)?;
+            }
+            Record::Polygon {
+                window,
+                id,
+                polygon: Some(state),
+            } => {
+                let registry = self.state.registry(&window)?;
+                let mut polygons = registry.polygons.write()?;
+                let polygon = polygons.entry(id.clone()).or_insert_with(|| {
+                    Polygon::new(&id, state.shape.clone(), registry.next_order())
+                });
+                polygon.set_geometry(Geometry::new(state.shape, state.padding, state.fill_rule));
+                polygon.set_z_index(state.z_index);
+                if state.visible {
+                    polygon.show();
+                } else {
+                    polygon.hide();
+                }
+                registry.index.write()?.insert(&id, polygon.bounds());
+            }
+            Record::Start { .. }
+            | Record::Input { .. }
+            | Record::Emit { .. }
+            | Record::IgnoreCursorEvents { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+/// An input event of a recording, after which the heuristics did other than the recorded app.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Line of the input event in the recording, starting at 1
+    pub line: usize,
+    pub input: InputEvent,
+    /// What the recorded app did
+    pub expected: Vec<Record>,
+    /// What the heuristics did in the replay
+    pub actual: Vec<Record>,
+}
+
+impl Divergence {
+    fn diverged(&self) -> bool {
+        self.expected != self.actual
+    }
+}
+
+impl From<Effect> for Record {
+    fn from(effect: Effect) -> Self {
+        match effect {
+            Effect::Event(event) => Record::emit(&event),
+            Effect::IgnoreCursorEvents { window, ignore } => {
+                Record::IgnoreCursorEvents { window, ignore }
+            }
+        }
+    }
+}
+
+/// Backend of the harness state, the harness feeds events itself.
+struct Scripted;
+
+impl InputBackend for Scripted {
+    fn start(self: Box<Self>, _sink: InputSink) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Time only passes with `Step::Wait`.
+struct ScriptedClock {
+    start: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl Default for ScriptedClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+}
+
+impl ScriptedClock {
+    fn advance(&self, duration: Duration) {
+        if let Ok(mut elapsed) = self.elapsed.lock() {
+            *elapsed += duration;
+        }
+    }
+    fn set(&self, duration: Duration) {
+        if let Ok(mut elapsed) = self.elapsed.lock() {
+            *elapsed = duration;
+        }
+    }
+}
+
+impl Clock for ScriptedClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed.lock().map(|e| *e).unwrap_or_default()
+    }
+}
+
+/// Collects effects, and writes them to the recording of the harness if there is one.
+struct Collector {
+    default_window: String,
+    effects: Mutex<Vec<Effect>>,
+    state: Arc<State>,
+}
+
+impl Collector {
+    fn push(&self, effect: Effect) {
+        if let Err(e) = self.state.recorder.write(|| effect.clone().into()) {
+            self.push(Effect::Event(Event::Error(e)));
+        }
+        if let Ok(mut effects) = self.effects.lock() {
+            effects.push(effect);
+        }
+    }
+}
+
+impl Output for Collector {
+    fn window_label(&self) -> &str {
+        &self.default_window
+    }
+    fn emit(&self, event: Event) {
+        self.push(Effect::Event(event));
+    }
+    fn set_ignore_cursor_events(&self, window: &str, ignore: bool) -> Result<()> {
+        self.push(Effect::IgnoreCursorEvents {
+            window: window.to_string(),
+            ignore,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grab::Position;
+
+    const SQUARE: [(f64, f64); 4] = [(0.0, 0.0), (200.0, 0.0), (200.0, 200.0), (0.0, 200.0)];
+
+    fn moved(x: f64, y: f64) -> Effect {
+        Effect::Event(Event::MouseMove {
+            x,
+            y,
+            monitor: Some(0),
+        })
+    }
+
+    fn left_click(x: f64, y: f64) -> Effect {
+        Effect::Event(Event::LeftClick {
+            x,
+            y,
+            topmost: None,
+            window: None,
+            monitor: Some(0),
+        })
+    }
+
+    fn click(press_for: u64) -> [Step; 3] {
+        [
+            Step::press(Button::Left),
+            Step::wait(press_for),
+            Step::release(Button::Left),
+        ]
+    }
+
+    #[test]
+    fn click_and_double_click() {
+        let harness = Harness::new();
+        harness.play([Step::move_to(500.0, 500.0), Step::wait(1000)]);
+        harness.play(click(50));
+        assert_eq!(
+            harness.take(),
+            vec![moved(500.0, 500.0), left_click(500.0, 500.0)]
+        );
+
+        harness.play([Step::wait(100)]);
+        harness.play(click(50));
+        assert_eq!(
+            harness.take(),
+            vec![Effect::Event(Event::DoubleClick {
+                x: 500.0,
+                y: 500.0,
+                topmost: None,
+                window: None,
+                monitor: Some(0),
+            })]
+        );
+
+        // too late for a double click
+        harness.play([Step::wait(500)]);
+        harness.play(click(50));
+        assert_eq!(harness.take(), vec![left_click(500.0, 500.0)]);
+    }
+
+    #[test]
+    fn long_press_in_place_is_a_click() {
+        let harness = Harness::new();
+        harness.play([Step::move_to(500.0, 500.0), Step::wait(1000)]);
+        harness.play(click(50));
+        harness.take();
+
+        harness.play([Step::wait(100)]);
+        harness.play(click(300));
+        assert_eq!(harness.take(), vec![left_click(500.0, 500.0)]);
+    }
+
+    #[test]
+    fn drag() {
+        let harness = Harness::new();
+        harness.play([
+            Step::move_to(100.0, 100.0),
+            Step::wait(1000),
+            Step::press(Button::Left),
+            Step::move_to(300.0, 200.0),
+            Step::wait(200),
+            Step::release(Button::Left),
+        ]);
+
+        assert_eq!(
+            harness.take(),
+            vec![
+                moved(100.0, 100.0),
+                moved(300.0, 200.0),
+                Effect::Event(Event::Drag {
+                    from: Position { x: 100.0, y: 100.0 },
+                    to: Position { x: 300.0, y: 200.0 },
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn clicks_in_polygons_report_the_topmost() {
+        let harness = Harness::new();
+        harness.polygon("main", "toolbar", &SQUARE).unwrap();
+        harness
+            .polygon(
+                "main",
+                "button",
+                &[(50.0, 50.0), (150.0, 50.0), (150.0, 150.0)],
+            )
+            .unwrap();
+        harness.play([Step::move_to(100.0, 20.0), Step::wait(1000)]);
+        assert_eq!(
+            harness.take(),
+            vec![
+                Effect::IgnoreCursorEvents {
+                    window: "main".into(),
+                    ignore: false,
+                },
+                Effect::Event(Event::MouseEnter {
+                    window: "main".into(),
+                    ids: vec!["toolbar".into()],
+                    topmost: "toolbar".into(),
+                }),
+                moved(100.0, 20.0),
+            ]
+        );
+
+        harness.play(click(50));
+        harness.play([Step::release(Button::Right)]);
+        assert_eq!(
+            harness.take(),
+            vec![
+                Effect::Event(Event::LeftClick {
+                    x: 100.0,
+                    y: 20.0,
+                    topmost: Some("toolbar".into()),
+                    window: Some("main".into()),
+                    monitor: Some(0),
+                }),
+                Effect::Event(Event::RightClick {
+                    x: 100.0,
+                    y: 20.0,
+                    topmost: Some("toolbar".into()),
+                    window: Some("main".into()),
+                    monitor: Some(0),
+                }),
+            ]
+        );
+
+        // where polygons overlap, the one registered last is on top unless raised
+        harness.play([Step::move_to(120.0, 100.0), Step::wait(1000)]);
+        harness.play(click(50));
+        assert_eq!(
+            harness.take().last(),
+            Some(&Effect::Event(Event::LeftClick {
+                x: 120.0,
+                y: 100.0,
+                topmost: Some("button".into()),
+                window: Some("main".into()),
+                monitor: Some(0),
+            }))
+        );
+        view::raise(&harness.state.registry("main").unwrap(), "toolbar").unwrap();
+        harness.play([Step::wait(1000)]);
+        harness.play(click(50));
+        assert_eq!(
+            harness.take(),
+            vec![Effect::Event(Event::LeftClick {
+                x: 120.0,
+                y: 100.0,
+                topmost: Some("toolbar".into()),
+                window: Some("main".into()),
+                monitor: Some(0),
+            })]
+        );
+
+        // drags in polygons are left to the window
+        harness.play([
+            Step::press(Button::Left),
+            Step::move_to(130.0, 100.0),
+            Step::wait(200),
+            Step::release(Button::Left),
+        ]);
+        assert_eq!(
+            harness.take(),
+            vec![
+                // the raised polygon became the topmost in the meantime
+                Effect::Event(Event::MouseEnter {
+                    window: "main".into(),
+                    ids: vec!["toolbar".into(), "button".into()],
+                    topmost: "toolbar".into(),
+                }),
+                moved(130.0, 100.0),
+            ]
+        );
+
+        harness.play([Step::move_to(500.0, 500.0), Step::release(Button::Right)]);
+        assert_eq!(
+            harness.take(),
+            vec![
+                Effect::IgnoreCursorEvents {
+                    window: "main".into(),
+                    ignore: true,
+                },
+                Effect::Event(Event::MouseLeave {
+                    window: "main".into(),
+                }),
+                moved(500.0, 500.0),
+                Effect::Event(Event::RightClick {
+                    x: 500.0,
+                    y: 500.0,
+                    topmost: None,
+                    window: None,
+                    monitor: Some(0),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn moving_between_windows() {
+        let harness = Harness::new().window("overlay", (1000.0, 0.0), (500.0, 500.0));
+        harness.polygon("overlay", "panel", &SQUARE).unwrap();
+
+        // only the window with the polygon under the cursor toggles
+        harness.play([Step::move_to(1100.0, 100.0)]);
+        assert_eq!(
+            harness.take(),
+            vec![
+                Effect::IgnoreCursorEvents {
+                    window: "overlay".into(),
+                    ignore: false,
+                },
+                Effect::Event(Event::MouseEnter {
+                    window: "overlay".into(),
+                    ids: vec!["panel".into()],
+                    topmost: "panel".into(),
+                }),
+                moved(1100.0, 100.0),
+            ]
+        );
+
+        // a polygon removed under the cursor is left on the next move
+        harness.remove("overlay", "panel").unwrap();
+        harness.play([Step::move_to(1101.0, 100.0)]);
+        assert_eq!(
+            harness.take(),
+            vec![
+                Effect::IgnoreCursorEvents {
+                    window: "overlay".into(),
+                    ignore: true,
+                },
+                Effect::Event(Event::MouseLeave {
+                    window: "overlay".into(),
+                }),
+                moved(1101.0, 100.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn clicks_report_the_window_of_the_topmost() {
+        let harness = Harness::new().window("overlay", (1000.0, 0.0), (500.0, 500.0));
+        harness.polygon("main", "toolbar", &SQUARE).unwrap();
+        harness.polygon("overlay", "toolbar", &SQUARE).unwrap();
+
+        // the same id in two windows, told apart by the window
+        for (x, window) in [(100.0, "main"), (1100.0, "overlay")] {
+            harness.play([Step::move_to(x, 100.0), Step::wait(1000)]);
+            harness.take();
+            harness.play(click(50));
+            assert_eq!(
+                harness.take(),
+                vec![Effect::Event(Event::LeftClick {
+                    x,
+                    y: 100.0,
+                    topmost: Some("toolbar".into()),
+                    window: Some(window.into()),
+                    monitor: Some(0),
+                })]
+            );
+        }
+    }
+
+    /// Record a session with a polygon which comes and goes under the cursor.
+    fn record(name: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("polygon-{}-{name}.jsonl", std::process::id()));
+        let harness = Harness::new();
+        harness.polygon("main", "toolbar", &SQUARE).unwrap();
+        harness.play([Step::move_to(100.0, 100.0)]);
+        harness.record(&path).unwrap();
+
+        harness.play(click(50));
+        harness.play([Step::move_to(500.0, 500.0), Step::wait(1000)]);
+        harness.play(click(50));
+        harness.play([Step::wait(100)]);
+        harness.play(click(50));
+        harness
+            .polygon(
+                "main",
+                "toolbar",
+                &[(400.0, 400.0), (600.0, 400.0), (600.0, 600.0)],
+            )
+            .unwrap();
+        harness.play([Step::move_to(550.0, 450.0)]);
+        harness.remove("main", "toolbar").unwrap();
+        harness.play([Step::move_to(551.0, 450.0)]);
+        harness.stop_recording().unwrap();
+        path
+    }
+
+    #[test]
+    fn replay_of_a_recording() {
+        let path = record("replay");
+        let divergences = Harness::replay(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(divergences.unwrap(), vec![]);
+    }
+
+    #[test]
+    fn replay_reports_divergences() {
+        let path = record("divergences");
+        // as if the recorded app did not let the cursor through the toolbar
+        let recording = std::fs::read_to_string(&path).unwrap();
+        let tampered = recording
+            .lines()
+            .filter(|line| !line.contains(r#""ignore":true"#))
+            .collect::<Vec<&str>>()
+            .join("\n");
+        std::fs::write(&path, tampered).unwrap();
+        let divergences = Harness::replay(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let divergences = divergences.unwrap();
+        assert_eq!(
+            divergences
+                .iter()
+                .map(|d| d.input)
+                .collect::<Vec<InputEvent>>(),
+            [
+                InputEvent::MouseMove { x: 500.0, y: 500.0 },
+                InputEvent::MouseMove { x: 551.0, y: 450.0 },
+            ]
+        );
+        for divergence in divergences {
+            assert_eq!(
+                divergence.actual[0],
+                Record::IgnoreCursorEvents {
+                    window: "main".into(),
+                    ignore: true,
+                }
+            );
+            assert_eq!(divergence.expected, divergence.actual[1..]);
+        }
+    }
+
+    #[test]
+    fn changes_are_recorded_as_they_are_made() {
+        let path =
+            std::env::temp_dir().join(format!("polygon-{}-changes.jsonl", std::process::id()));
+        let harness = Harness::new();
+        harness.polygon("main", "toolbar", &SQUARE).unwrap();
+        harness.polygon("main", "button", &SQUARE).unwrap();
+        harness.record(&path).unwrap();
+
+        // without input in between, none of them is merged into the next one
+        let registry = harness.state.registry("main").unwrap();
+        view::hide(&registry, "toolbar").unwrap();
+        view::show(&registry, "toolbar").unwrap();
+        view::clear(&registry).unwrap();
+        harness.stop_recording().unwrap();
+
+        let recording = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let polygons = recording
+            .lines()
+            .map(|line| serde_json::from_str::<Record>(line).unwrap())
+            .filter_map(|record| match record {
+                Record::Polygon { id, polygon, .. } => {
+                    Some((id, polygon.map(|polygon| polygon.visible)))
+                }
+                _ => None,
+            })
+            .collect::<Vec<(String, Option<bool>)>>();
+        assert_eq!(
+            polygons,
+            [
+                ("toolbar".to_string(), Some(true)),
+                ("button".to_string(), Some(true)),
+                ("toolbar".to_string(), Some(false)),
+                ("toolbar".to_string(), Some(true)),
+                // in creation order
+                ("toolbar".to_string(), None),
+                ("button".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_needs_a_start() {
+        let path = std::env::temp_dir().join(format!("polygon-{}-start.jsonl", std::process::id()));
+        std::fs::write(&path, r#"{"kind":"coordinateMode","mode":"physical"}"#).unwrap();
+        let result = Harness::replay(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(Error::RecordingError(_))));
+    }
+}
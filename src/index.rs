@@ -0,0 +1,220 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::polygon::{Bounds, PolygonId};
+
+/// Side length of a grid cell, in physical pixels
+const CELL_SIZE: f64 = 64.0;
+/// Polygons spanning more cells than this are not split into cells,
+/// they are kept aside and tested against every point instead
+const MAX_CELLS: i64 = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Slot {
+    /// Inclusive range of cells, `(min_col, min_row, max_col, max_row)`
+    Cells(i64, i64, i64, i64),
+    Large,
+}
+
+/// A uniform grid over the bounding boxes of polygons,
+/// so hit testing only needs to examine polygons near the cursor.
+#[derive(Debug, Default)]
+pub struct Grid {
+    cells: HashMap<(i64, i64), HashSet<PolygonId>>,
+    large: HashSet<PolygonId>,
+    slots: HashMap<PolygonId, Slot>,
+}
+
+impl Grid {
+    /// Insert the polygon, or move it if it has been indexed.
+    /// Polygons with empty bounds are not indexed at all.
+    pub fn insert(&mut self, id: &str, bounds: Bounds) {
+        self.remove(id);
+
+        if bounds.is_empty() {
+            return;
+        }
+
+        let (min_col, min_row) = (cell(bounds.min_x), cell(bounds.min_y));
+        let (max_col, max_row) = (cell(bounds.max_x), cell(bounds.max_y));
+        let count = (max_col.saturating_sub(min_col).saturating_add(1))
+            .saturating_mul(max_row.saturating_sub(min_row).saturating_add(1));
+
+        let finite = [bounds.min_x, bounds.min_y, bounds.max_x, bounds.max_y]
+            .iter()
+            .all(|v| v.is_finite());
+
+        if !finite || count > MAX_CELLS {
+            self.large.insert(id.to_string());
+            self.slots.insert(id.to_string(), Slot::Large);
+            return;
+        }
+
+        for col in min_col..=max_col {
+            for row in min_row..=max_row {
+                self.cells
+                    .entry((col, row))
+                    .or_default()
+                    .insert(id.to_string());
+            }
+        }
+        self.slots.insert(
+            id.to_string(),
+            Slot::Cells(min_col, min_row, max_col, max_row),
+        );
+    }
+    pub fn remove(&mut self, id: &str) {
+        match self.slots.remove(id) {
+            Some(Slot::Cells(min_col, min_row, max_col, max_row)) => {
+                for col in min_col..=max_col {
+                    for row in min_row..=max_row {
+                        if let Some(ids) = self.cells.get_mut(&(col, row)) {
+                            ids.remove(id);
+                            if ids.is_empty() {
+                                self.cells.remove(&(col, row));
+                            }
+                        }
+                    }
+                }
+            }
+            Some(Slot::Large) => {
+                self.large.remove(id);
+            }
+            None => {}
+        }
+    }
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.large.clear();
+        self.slots.clear();
+    }
+    /// Polygons whose bounding box may contain the point.
+    pub fn query(&self, point: (f64, f64)) -> impl Iterator<Item = &PolygonId> {
+        self.cells
+            .get(&(cell(point.0), cell(point.1)))
+            .into_iter()
+            .flatten()
+            .chain(self.large.iter())
+    }
+}
+
+fn cell(v: f64) -> i64 {
+    // `as` saturates, which keeps far away points in the outermost cells
+    (v / CELL_SIZE).floor() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Bounds {
+        Bounds {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    fn hits(grid: &Grid, point: (f64, f64)) -> Vec<&str> {
+        let mut ids = grid.query(point).map(String::as_str).collect::<Vec<&str>>();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn polygons_span_several_cells() {
+        let mut grid = Grid::default();
+        grid.insert("wide", bounds(10.0, 10.0, 200.0, 100.0));
+
+        // 4 columns and 2 rows of cells
+        assert_eq!(grid.cells.len(), 8);
+        for point in [(10.0, 10.0), (100.0, 50.0), (200.0, 100.0), (255.0, 127.0)] {
+            assert_eq!(hits(&grid, point), ["wide"], "{point:?}");
+        }
+        // next to the cells the bounds touch
+        for point in [(256.0, 50.0), (100.0, 128.0), (-1.0, 50.0)] {
+            assert!(hits(&grid, point).is_empty(), "{point:?}");
+        }
+    }
+
+    #[test]
+    fn insert_moves_an_indexed_polygon() {
+        let mut grid = Grid::default();
+        grid.insert("moving", bounds(0.0, 0.0, 100.0, 100.0));
+        grid.insert("still", bounds(0.0, 0.0, 10.0, 10.0));
+        grid.insert("moving", bounds(1000.0, 1000.0, 1010.0, 1010.0));
+
+        assert_eq!(hits(&grid, (5.0, 5.0)), ["still"]);
+        assert!(hits(&grid, (100.0, 100.0)).is_empty());
+        assert_eq!(hits(&grid, (1005.0, 1005.0)), ["moving"]);
+        // the cells it left are gone, not kept empty
+        assert_eq!(grid.cells.len(), 2);
+    }
+
+    #[test]
+    fn remove() {
+        let mut grid = Grid::default();
+        grid.insert("a", bounds(0.0, 0.0, 100.0, 100.0));
+        grid.insert("b", bounds(50.0, 50.0, 60.0, 60.0));
+        grid.insert("huge", bounds(0.0, 0.0, 1e6, 1e6));
+
+        grid.remove("a");
+        grid.remove("huge");
+        // removing twice, or an unknown id, is fine
+        grid.remove("a");
+        grid.remove("unknown");
+
+        assert_eq!(hits(&grid, (55.0, 55.0)), ["b"]);
+        assert!(hits(&grid, (100.0, 100.0)).is_empty());
+        assert_eq!(grid.cells.len(), 1);
+        assert!(grid.large.is_empty());
+        assert_eq!(grid.slots.len(), 1);
+    }
+
+    #[test]
+    fn negative_coordinates_floor_to_the_cell_below() {
+        let mut grid = Grid::default();
+        // -64 starts cell -1, and -64.5 is in cell -2
+        grid.insert("left", bounds(-64.0, -64.0, -1.0, -1.0));
+
+        assert_eq!(grid.slots["left"], Slot::Cells(-1, -1, -1, -1));
+        assert_eq!(hits(&grid, (-64.0, -64.0)), ["left"]);
+        assert_eq!(hits(&grid, (-0.5, -0.5)), ["left"]);
+        assert!(hits(&grid, (-64.5, -10.0)).is_empty());
+        assert!(hits(&grid, (0.0, -10.0)).is_empty());
+    }
+
+    #[test]
+    fn large_polygons_are_kept_aside() {
+        let mut grid = Grid::default();
+        // 64 x 64 cells is exactly the limit, one more column exceeds it
+        grid.insert("limit", bounds(0.0, 0.0, 4095.0, 4095.0));
+        grid.insert("large", bounds(0.0, 0.0, 4096.0, 4095.0));
+        grid.insert("infinite", bounds(0.0, 0.0, f64::INFINITY, 10.0));
+
+        assert!(matches!(grid.slots["limit"], Slot::Cells(..)));
+        assert_eq!(grid.slots["large"], Slot::Large);
+        assert_eq!(grid.slots["infinite"], Slot::Large);
+        assert_eq!(grid.cells.len(), 4096);
+        // large ones are candidates for every point
+        assert_eq!(hits(&grid, (-1e9, 1e9)), ["infinite", "large"]);
+        assert_eq!(hits(&grid, (10.0, 10.0)), ["infinite", "large", "limit"]);
+    }
+
+    #[test]
+    fn empty_bounds_are_not_indexed() {
+        let mut grid = Grid::default();
+        grid.insert("empty", Bounds::EMPTY);
+        grid.insert("inverted", bounds(10.0, 10.0, 0.0, 0.0));
+
+        assert!(grid.cells.is_empty());
+        assert!(grid.large.is_empty());
+        assert!(grid.slots.is_empty());
+
+        // an indexed polygon which becomes empty is taken out
+        grid.insert("shrinking", bounds(0.0, 0.0, 10.0, 10.0));
+        grid.insert("shrinking", Bounds::EMPTY);
+        assert!(hits(&grid, (5.0, 5.0)).is_empty());
+        assert!(grid.slots.is_empty());
+    }
+}
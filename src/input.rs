@@ -0,0 +1,204 @@
+use std::{sync::Arc, thread};
+
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::polygon::Bounds;
+use crate::state::State;
+
+/// Pointer events fed into the plugin, positions in physical pixels relative to the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum InputEvent {
+    MouseMove { x: f64, y: f64 },
+    ButtonPress { button: Button },
+    ButtonRelease { button: Button },
+    Wheel { delta_x: f64, delta_y: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Button {
+    Left,
+    Right,
+    Middle,
+    Other,
+}
+
+/// Receives events from an `InputBackend`, and turns them into clicks, drags,
+/// and cursor events of polygons. Cheap to clone, and can be sent across threads.
+#[derive(Clone)]
+pub struct InputSink {
+    f: Arc<dyn Fn(Result<InputEvent>) + Send + Sync>,
+    state: Arc<State>,
+}
+
+impl InputSink {
+    pub(crate) fn new<F: Fn(Result<InputEvent>) + Send + Sync + 'static>(
+        state: Arc<State>,
+        f: F,
+    ) -> Self {
+        Self {
+            f: Arc::new(f),
+            state,
+        }
+    }
+    pub fn send(&self, event: InputEvent) {
+        (self.f)(Ok(event));
+    }
+    /// Report a failure of the backend, emitted as `Event::Error`.
+    pub fn error(&self, error: Error) {
+        (self.f)(Err(error));
+    }
+    /// Bounding box of all monitors in physical pixels, `None` until monitors are known.
+    /// Backends reading relative motion can clamp the cursor to it.
+    pub fn screen(&self) -> Option<Bounds> {
+        self.state.screen()
+    }
+}
+
+/// A source of pointer events, see `Builder::input`.
+///
+/// # Example
+/// ```no_run
+/// use tauri_plugin_polygon::{InputBackend, InputEvent, InputSink};
+///
+/// struct Replay(Vec<InputEvent>);
+///
+/// impl InputBackend for Replay {
+///     fn start(self: Box<Self>, sink: InputSink) -> tauri_plugin_polygon::Result<()> {
+///         std::thread::spawn(move || self.0.into_iter().for_each(|event| sink.send(event)));
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait InputBackend: Send + 'static {
+    /// Start feeding events to `sink`, normally on a thread of its own.
+    /// Called once, when the first window polygons belong to is ready.
+    ///
+    /// # Errors
+    /// Returns an error if the backend can not be started, failures later on
+    /// should be reported with `InputSink::error`.
+    fn start(self: Box<Self>, sink: InputSink) -> Result<()>;
+}
+
+/// How `RdevBackend` receives input events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InputMode {
+    /// Intercept events with an exclusive grab, which needs access to `/dev/input` on Linux.
+    /// Only available with the `grab` feature.
+    Grab,
+    /// Observe events passively, without extra privileges. Needs X11 on Linux.
+    Listen,
+    /// `Grab` if it is available, `Listen` otherwise.
+    #[default]
+    Auto,
+}
+
+/// Receives global input events with `rdev`, which is the default backend.
+///
+/// Events are never swallowed, so `InputMode::Listen` behaves the same as a grab.
+/// Neither mode works on Wayland.
+#[derive(Debug, Default)]
+pub struct RdevBackend {
+    mode: InputMode,
+}
+
+impl RdevBackend {
+    pub fn new(mode: InputMode) -> Self {
+        Self { mode }
+    }
+}
+
+impl InputBackend for RdevBackend {
+    fn start(self: Box<Self>, sink: InputSink) -> Result<()> {
+        let mode = self.mode;
+        if mode == InputMode::Grab && !cfg!(feature = "grab") {
+            return Err(Error::InputUnavailable(
+                "InputMode::Grab needs the `grab` feature.".into(),
+            ));
+        }
+
+        thread::Builder::new()
+            .name("polygon-input".to_string())
+            .spawn(move || {
+                let result = match mode {
+                    InputMode::Grab => grab(&sink),
+                    InputMode::Listen => listen(&sink),
+                    InputMode::Auto => grab(&sink).or_else(|grab_error| {
+                        warn!("{grab_error} Listening to events instead.");
+                        listen(&sink).map_err(|listen_error| format!("{grab_error} {listen_error}"))
+                    }),
+                };
+
+                if let Err(e) = result {
+                    error!("Failed to receive input events: {e}");
+                    sink.error(Error::InputUnavailable(e));
+                }
+            })?;
+        Ok(())
+    }
+}
+
+/// Blocks until grabbing fails.
+#[cfg(feature = "grab")]
+fn grab(sink: &InputSink) -> std::result::Result<(), String> {
+    let sink = sink.clone();
+    rdev::grab(move |ev| {
+        if let Some(event) = from_rdev(&ev.event_type) {
+            sink.send(event);
+        }
+        Some(ev)
+    })
+    .map_err(|e| format!("Failed to grab events: {e:?}."))
+}
+
+#[cfg(not(feature = "grab"))]
+fn grab(_sink: &InputSink) -> std::result::Result<(), String> {
+    Err("Grabbing events needs the `grab` feature.".into())
+}
+
+/// Blocks until listening fails.
+fn listen(sink: &InputSink) -> std::result::Result<(), String> {
+    let sink = sink.clone();
+    rdev::listen(move |ev| {
+        if let Some(event) = from_rdev(&ev.event_type) {
+            sink.send(event);
+        }
+    })
+    .map_err(|e| format!("Failed to listen to events: {e:?}."))
+}
+
+fn from_rdev(event: &rdev::EventType) -> Option<InputEvent> {
+    match *event {
+        rdev::EventType::MouseMove { x, y } => Some(InputEvent::MouseMove { x, y }),
+        rdev::EventType::ButtonPress(button) => Some(InputEvent::ButtonPress {
+            button: button.into(),
+        }),
+        rdev::EventType::ButtonRelease(button) => Some(InputEvent::ButtonRelease {
+            button: button.into(),
+        }),
+        rdev::EventType::Wheel { delta_x, delta_y } => Some(InputEvent::Wheel {
+            delta_x: delta_x as f64,
+            delta_y: delta_y as f64,
+        }),
+        _ => None,
+    }
+}
+
+impl From<rdev::Button> for Button {
+    fn from(button: rdev::Button) -> Self {
+        match button {
+            rdev::Button::Left => Button::Left,
+            rdev::Button::Right => Button::Right,
+            rdev::Button::Middle => Button::Middle,
+            rdev::Button::Unknown(_) => Button::Other,
+        }
+    }
+}
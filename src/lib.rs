@@ -1,23 +1,44 @@
 use tauri::AppHandle;
 #[cfg(desktop)]
 use tauri::{
-    plugin::{Builder, TauriPlugin},
+    plugin::{Builder as PluginBuilder, TauriPlugin},
     Manager, Runtime,
 };
 
 mod commands;
 mod desktop;
+mod display;
 mod error;
+#[cfg(all(target_os = "linux", feature = "evdev"))]
+mod evdev;
 mod grab;
+#[cfg(any(test, feature = "test-harness"))]
+mod harness;
+mod index;
+mod input;
 mod models;
 mod polygon;
-mod statics;
+mod record;
+mod registry;
+mod state;
+mod svg;
 mod utils;
+mod validation;
 mod view;
 
 pub use desktop::Polygon;
 pub use error::{Error, Result};
-pub use grab::Event;
+#[cfg(all(target_os = "linux", feature = "evdev"))]
+pub use evdev::EvdevBackend;
+pub use grab::{Event, Position};
+#[cfg(feature = "test-harness")]
+pub use harness::{Divergence, Effect, Harness, Step};
+pub use input::{Button, InputBackend, InputEvent, InputMode, InputSink, RdevBackend};
+pub use models::{Config, PolygonInfo};
+pub use polygon::{Bounds, FillRule, Points, Shape, Transform};
+#[cfg(feature = "test-harness")]
+pub use record::{PolygonState, Record};
+pub use utils::CoordinateMode;
 
 /// Extensions to [`tauri::App`], [`tauri::AppHandle`] and [`tauri::Window`] to access the polygon APIs.
 pub trait PolygonExt<R: Runtime> {
@@ -31,35 +52,124 @@ impl<R: Runtime, T: Manager<R>> crate::PolygonExt<R> for T {
 }
 
 /// Initializes the plugin.
+///
+/// `f` is called with every event on a thread of its own, one event at a time,
+/// so it may block or call `Polygon` methods.
 pub fn init<R: Runtime, F: FnMut(&AppHandle<R>, crate::Event) + Send + Sync + 'static>(
     f: F,
-) -> TauriPlugin<R> {
-    Builder::new("polygon")
-        .invoke_handler(tauri::generate_handler![
-            commands::register,
-            commands::register_all,
-            commands::remove,
-            commands::show,
-            commands::hide,
-            commands::update,
-            commands::clear
-        ])
-        .setup(|app, api| {
-            let polygon = desktop::init(app, api, Box::new(f))?;
-            app.manage(polygon);
-            Ok(())
-        })
-        .on_window_ready(move |win| {
-            if win.label() == "main" {
-                statics::init(win.clone());
-                grab::init(win.clone());
-            }
-        })
-        .on_drop(|app| {
-            // Clear all polygon in cache
-            let _ = app.state::<Polygon<R>>().inner().clear();
-            // drop callback
-            let _ = app.state::<Polygon<R>>().inner().destroy();
-        })
-        .build()
+) -> TauriPlugin<R, Option<Config>> {
+    Builder::new(f).build()
+}
+
+/// Builds the plugin with options, `init` uses the defaults.
+///
+/// # Example
+/// ```no_run
+/// tauri::Builder::default()
+///     .plugin(
+///         tauri_plugin_polygon::Builder::new(|_app, _event| {})
+///             .window("overlay")
+///             .window("overlay-2")
+///             .build(),
+///     );
+/// ```
+pub struct Builder<R: Runtime> {
+    callback: desktop::PolygonCallback<R>,
+    windows: Vec<String>,
+    backend: Option<Box<dyn InputBackend>>,
+    input_mode: Option<InputMode>,
+}
+
+impl<R: Runtime> Builder<R> {
+    pub fn new<F: FnMut(&AppHandle<R>, crate::Event) + Send + Sync + 'static>(f: F) -> Self {
+        Self {
+            callback: Box::new(f),
+            windows: Vec::new(),
+            backend: None,
+            input_mode: None,
+        }
+    }
+    /// Label of a window polygons belong to, call it again for more windows with polygons of their own.
+    /// The first one is the default window, see `Polygon::window`.
+    /// Takes precedence over `plugins.polygon` in `tauri.conf.json`, `"main"` by default.
+    pub fn window(mut self, label: impl Into<String>) -> Self {
+        self.windows.push(label.into());
+        self
+    }
+    /// Source of pointer events, `RdevBackend` by default.
+    pub fn input(mut self, backend: impl InputBackend) -> Self {
+        self.backend = Some(Box::new(backend));
+        self
+    }
+    /// How the default backend receives input events, see `InputMode`.
+    /// Takes precedence over `plugins.polygon.inputMode` in `tauri.conf.json`, `InputMode::Auto` by default.
+    pub fn input_mode(mut self, mode: InputMode) -> Self {
+        self.input_mode = Some(mode);
+        self
+    }
+    pub fn build(self) -> TauriPlugin<R, Option<Config>> {
+        let Builder {
+            callback,
+            windows,
+            backend,
+            input_mode,
+        } = self;
+        PluginBuilder::<R, Option<Config>>::new("polygon")
+            .invoke_handler(tauri::generate_handler![
+                commands::register,
+                commands::register_all,
+                commands::remove,
+                commands::show,
+                commands::hide,
+                commands::update,
+                commands::update_with_holes,
+                commands::update_svg_path,
+                commands::update_circle,
+                commands::update_ellipse,
+                commands::update_rounded_rect,
+                commands::translate,
+                commands::scale,
+                commands::rotate,
+                commands::transform,
+                commands::set_z_index,
+                commands::raise,
+                commands::lower,
+                commands::hit_test,
+                commands::exists,
+                commands::get,
+                commands::list,
+                commands::set_metadata,
+                commands::clear,
+                commands::set_normalization,
+                commands::set_coordinate_mode
+            ])
+            .setup(move |app, api| {
+                let polygon = desktop::init(app, api, callback, windows, backend, input_mode)?;
+                app.manage(polygon);
+                Ok(())
+            })
+            .on_window_ready(move |win| {
+                let label = win.label().to_string();
+                if win.polygon().window_labels().contains(&label) {
+                    // also called when a window is created again, which keeps its polygons
+                    let state = win.polygon().state().clone();
+                    if let Err(e) = state.init(&win) {
+                        grab::emit(win.app_handle(), Event::Error(e));
+                        return;
+                    }
+                    display::watch(win.clone(), state.clone());
+                    grab::init(win.clone(), state);
+                }
+            })
+            .on_drop(|app| {
+                // Clear all polygon in cache
+                let polygon = app.state::<Polygon<R>>();
+                for label in polygon.window_labels() {
+                    let _ = polygon.window(label).clear();
+                }
+                // drop callback
+                let _ = app.state::<Polygon<R>>().inner().destroy();
+            })
+            .build()
+    }
 }
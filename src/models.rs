@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::error::Error;
+use crate::input::InputMode;
+use crate::polygon::{FillRule, Shape};
 
 pub(crate) type CommandResult = Result<Response, Response>;
 
@@ -9,6 +11,8 @@ pub(crate) type CommandResult = Result<Response, Response>;
 pub(crate) struct Response {
     ok: bool,
     error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
 }
 
 impl Response {
@@ -16,12 +20,70 @@ impl Response {
         Ok(Self {
             ok: true,
             error: None,
+            data: None,
         })
     }
+    pub(crate) fn data<T: Serialize>(data: T) -> CommandResult {
+        match serde_json::to_value(data) {
+            Ok(data) => Ok(Self {
+                ok: true,
+                error: None,
+                data: Some(data),
+            }),
+            Err(e) => Err(Self {
+                ok: false,
+                error: Some(e.to_string()),
+                data: None,
+            }),
+        }
+    }
     pub(crate) fn err(error: Error) -> CommandResult {
         Err(Self {
             ok: false,
             error: Some(error.to_string()),
+            data: None,
         })
     }
 }
+
+/// A snapshot of the state of a registered polygon.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolygonInfo {
+    pub id: String,
+    /// The hit region, in the same percentage based units as `update`
+    pub shape: Shape,
+    /// Whether the polygon is shown, hidden polygons never capture the cursor
+    pub visible: bool,
+    /// Whether the cursor is in the polygon
+    pub hovered: bool,
+    pub fill_rule: FillRule,
+    /// In the same percentage based units as `shape`
+    pub padding: f64,
+    pub z_index: i32,
+    /// Data attached with `Polygon::set_metadata`
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// Plugin configuration, read from `plugins.polygon` in `tauri.conf.json`.
+///
+/// ```json5
+/// "plugins": {
+///   "polygon": {
+///     "window": "overlay",
+///     "windows": ["overlay-2"],
+///     "inputMode": "listen"
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    /// Label of the default window polygons belong to, `"main"` by default
+    pub window: Option<String>,
+    /// Labels of other windows with polygons of their own
+    #[serde(default)]
+    pub windows: Vec<String>,
+    /// How input events are received, `"auto"` by default
+    pub input_mode: Option<InputMode>,
+}
@@ -1,14 +1,501 @@
-use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use std::sync::{
+    atomic::{AtomicBool, AtomicI32, Ordering},
+    Arc,
+};
 
-use portable_atomic::AtomicF64;
+use arc_swap::{ArcSwap, ArcSwapOption, Guard};
+use serde::{Deserialize, Serialize};
+
+use crate::utils;
 
 pub type PolygonId = String;
 
+/// Vertices of a polygon, either a single ring or a list of disjoint rings
+/// which are treated as one logical polygon.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Points {
+    Single(Vec<(f64, f64)>),
+    Multiple(Vec<Vec<(f64, f64)>>),
+}
+
+impl Points {
+    pub fn into_rings(self) -> Vec<Vec<(f64, f64)>> {
+        match self {
+            Points::Single(ring) => vec![ring],
+            Points::Multiple(rings) => rings,
+        }
+    }
+}
+
+impl From<Vec<(f64, f64)>> for Points {
+    fn from(ring: Vec<(f64, f64)>) -> Self {
+        Points::Single(ring)
+    }
+}
+
+impl From<Vec<Vec<(f64, f64)>>> for Points {
+    fn from(rings: Vec<Vec<(f64, f64)>>) -> Self {
+        Points::Multiple(rings)
+    }
+}
+
+/// The rule used to decide whether a point is inside the rings of a polygon.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FillRule {
+    /// A point is inside if a ray from it crosses the outline an odd number of times.
+    /// Self-overlapping areas become holes.
+    #[default]
+    EvenOdd,
+    /// A point is inside if the outline winds around it at least once.
+    /// Self-overlapping areas stay filled.
+    NonZero,
+}
+
+impl From<u8> for FillRule {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => FillRule::NonZero,
+            _ => FillRule::EvenOdd,
+        }
+    }
+}
+
+impl From<FillRule> for u8 {
+    fn from(value: FillRule) -> Self {
+        match value {
+            FillRule::EvenOdd => 0,
+            FillRule::NonZero => 1,
+        }
+    }
+}
+
+/// Axis-aligned bounding box, saved as physical pixel numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bounds {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Bounds {
+    /// Bounds containing nothing, the identity of `union`.
+    pub const EMPTY: Bounds = Bounds {
+        min_x: f64::INFINITY,
+        min_y: f64::INFINITY,
+        max_x: f64::NEG_INFINITY,
+        max_y: f64::NEG_INFINITY,
+    };
+
+    pub fn from_points<'a, I: IntoIterator<Item = &'a (f64, f64)>>(points: I) -> Self {
+        points
+            .into_iter()
+            .fold(Self::EMPTY, |bounds, (x, y)| Bounds {
+                min_x: bounds.min_x.min(*x),
+                min_y: bounds.min_y.min(*y),
+                max_x: bounds.max_x.max(*x),
+                max_y: bounds.max_y.max(*y),
+            })
+    }
+    pub fn is_empty(&self) -> bool {
+        !(self.min_x <= self.max_x && self.min_y <= self.max_y)
+    }
+    /// Grow the bounds by `amount` on every side, negative amounts are ignored.
+    pub fn inflate(&self, amount: f64) -> Self {
+        let amount = amount.max(0.0);
+        Bounds {
+            min_x: self.min_x - amount,
+            min_y: self.min_y - amount,
+            max_x: self.max_x + amount,
+            max_y: self.max_y + amount,
+        }
+    }
+    pub fn contains(&self, point: (f64, f64)) -> bool {
+        point.0 >= self.min_x
+            && point.0 <= self.max_x
+            && point.1 >= self.min_y
+            && point.1 <= self.max_y
+    }
+}
+
+/// A 2D affine transform, in the same order as a CSS `matrix(a, b, c, d, e, f)`:
+///
+/// ```text
+/// x' = a * x + c * y + e
+/// y' = b * x + d * y + f
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(from = "[f64; 6]", into = "[f64; 6]")]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    pub fn translate(dx: f64, dy: f64) -> Self {
+        Transform {
+            e: dx,
+            f: dy,
+            ..Self::IDENTITY
+        }
+    }
+    /// Scale by `sx` and `sy` about `origin`.
+    pub fn scale(sx: f64, sy: f64, origin: (f64, f64)) -> Self {
+        Self::about(
+            Transform {
+                a: sx,
+                d: sy,
+                ..Self::IDENTITY
+            },
+            origin,
+        )
+    }
+    /// Rotate by `degrees` about `origin`. As with CSS, positive angles turn clockwise on screen,
+    /// since the y axis points down.
+    pub fn rotate(degrees: f64, origin: (f64, f64)) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self::about(
+            Transform {
+                a: cos,
+                b: sin,
+                c: -sin,
+                d: cos,
+                ..Self::IDENTITY
+            },
+            origin,
+        )
+    }
+    /// Apply `self`, then `next`.
+    pub fn then(&self, next: &Transform) -> Self {
+        Transform {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f,
+        }
+    }
+    pub fn apply(&self, point: (f64, f64)) -> (f64, f64) {
+        (
+            self.a * point.0 + self.c * point.1 + self.e,
+            self.b * point.0 + self.d * point.1 + self.f,
+        )
+    }
+    pub fn is_finite(&self) -> bool {
+        [self.a, self.b, self.c, self.d, self.e, self.f]
+            .iter()
+            .all(|v| v.is_finite())
+    }
+    fn about(linear: Transform, origin: (f64, f64)) -> Self {
+        Self::translate(-origin.0, -origin.1)
+            .then(&linear)
+            .then(&Self::translate(origin.0, origin.1))
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl From<[f64; 6]> for Transform {
+    fn from([a, b, c, d, e, f]: [f64; 6]) -> Self {
+        Transform { a, b, c, d, e, f }
+    }
+}
+
+impl From<Transform> for [f64; 6] {
+    fn from(t: Transform) -> Self {
+        [t.a, t.b, t.c, t.d, t.e, t.f]
+    }
+}
+
+/// The hit region of a polygon, saved as physical pixel numbers.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Shape {
+    /// One or more outer rings plus any number of hole rings,
+    /// at least 3 points needed for each ring
+    Polygon {
+        rings: Vec<Vec<(f64, f64)>>,
+        holes: Vec<Vec<(f64, f64)>>,
+    },
+    Circle {
+        center: (f64, f64),
+        radius: f64,
+    },
+    Ellipse {
+        center: (f64, f64),
+        radius_x: f64,
+        radius_y: f64,
+    },
+    /// `(x, y)` is the top-left corner of the rectangle
+    RoundedRect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radius: f64,
+    },
+}
+
+impl Shape {
+    /// `fill_rule` only matters to `Shape::Polygon`.
+    pub fn contains(&self, point: (f64, f64), fill_rule: FillRule) -> bool {
+        match self {
+            Shape::Polygon { rings, holes } => {
+                utils::is_point_in_rings(rings, point, fill_rule)
+                    && !holes.iter().any(|hole| {
+                        utils::is_point_in_rings(std::slice::from_ref(hole), point, fill_rule)
+                    })
+            }
+            Shape::Circle { center, radius } => utils::is_point_in_circle(*center, *radius, point),
+            Shape::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } => utils::is_point_in_ellipse(*center, *radius_x, *radius_y, point),
+            Shape::RoundedRect {
+                x,
+                y,
+                width,
+                height,
+                radius,
+            } => utils::is_point_in_rounded_rect((*x, *y, *width, *height), *radius, point),
+        }
+    }
+    /// Distance from the point to the outline of the shape, whether the point is inside or not.
+    pub fn distance_to_boundary(&self, point: (f64, f64)) -> f64 {
+        match self {
+            Shape::Polygon { rings, holes } => {
+                utils::distance_to_rings(rings.iter().chain(holes.iter()), point)
+            }
+            Shape::Circle { center, radius } => utils::distance_to_circle(*center, *radius, point),
+            Shape::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } => utils::distance_to_ellipse(*center, *radius_x, *radius_y, point),
+            Shape::RoundedRect {
+                x,
+                y,
+                width,
+                height,
+                radius,
+            } => utils::distance_to_rounded_rect((*x, *y, *width, *height), *radius, point),
+        }
+    }
+    /// Map the shape through the transform.
+    ///
+    /// Circles, ellipses and rounded rects keep their kind as long as the transform can be
+    /// expressed by it, e.g. translating or uniformly scaling. Otherwise, e.g. rotating an
+    /// ellipse by 45 degrees, the outline is flattened into a polygon first.
+    pub fn transform(&self, t: &Transform) -> Shape {
+        // sin and cos of right angles are not exactly 0
+        let zero = |v: f64| v.abs() < 1e-12;
+        let same = |u: f64, v: f64| zero(u.abs() - v.abs());
+        let axis_aligned = zero(t.b) && zero(t.c);
+        let axis_swapped = zero(t.a) && zero(t.d);
+
+        match self {
+            Shape::Polygon { rings, holes } => {
+                let map = |rings: &Vec<Vec<(f64, f64)>>| {
+                    rings
+                        .iter()
+                        .map(|ring| ring.iter().map(|p| t.apply(*p)).collect())
+                        .collect()
+                };
+                Shape::Polygon {
+                    rings: map(rings),
+                    holes: map(holes),
+                }
+            }
+            // a similarity keeps circles round
+            Shape::Circle { center, radius }
+                if same(t.a, t.d) && same(t.b, t.c) && zero(t.a * t.b + t.c * t.d) =>
+            {
+                Shape::Circle {
+                    center: t.apply(*center),
+                    radius: radius * (t.a * t.d - t.b * t.c).abs().sqrt(),
+                }
+            }
+            Shape::Circle { center, radius } => Shape::Ellipse {
+                center: *center,
+                radius_x: *radius,
+                radius_y: *radius,
+            }
+            .transform(t),
+            Shape::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } if axis_aligned || axis_swapped => {
+                let (radius_x, radius_y) = if axis_aligned {
+                    (radius_x * t.a.abs(), radius_y * t.d.abs())
+                } else {
+                    (radius_y * t.c.abs(), radius_x * t.b.abs())
+                };
+                Shape::Ellipse {
+                    center: t.apply(*center),
+                    radius_x,
+                    radius_y,
+                }
+            }
+            Shape::RoundedRect {
+                x,
+                y,
+                width,
+                height,
+                radius,
+            } if (axis_aligned && same(t.a, t.d)) || (axis_swapped && same(t.b, t.c)) => {
+                let corners = [t.apply((*x, *y)), t.apply((x + width, y + height))];
+                let bounds = Bounds::from_points(corners.iter());
+                Shape::RoundedRect {
+                    x: bounds.min_x,
+                    y: bounds.min_y,
+                    width: bounds.max_x - bounds.min_x,
+                    height: bounds.max_y - bounds.min_y,
+                    radius: radius * (t.a.abs() + t.b.abs()),
+                }
+            }
+            _ => self.to_polygon().transform(t),
+        }
+    }
+    /// Flatten the outline into rings, within a fraction of a physical pixel.
+    pub fn to_polygon(&self) -> Shape {
+        let ring = match self {
+            Shape::Polygon { .. } => return self.clone(),
+            Shape::Circle { center, radius } => utils::ellipse_to_ring(*center, *radius, *radius),
+            Shape::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } => utils::ellipse_to_ring(*center, *radius_x, *radius_y),
+            Shape::RoundedRect {
+                x,
+                y,
+                width,
+                height,
+                radius,
+            } => utils::rounded_rect_to_ring((*x, *y, *width, *height), *radius),
+        };
+        Shape::Polygon {
+            rings: vec![ring],
+            holes: Vec::new(),
+        }
+    }
+    pub fn bounds(&self) -> Bounds {
+        match self {
+            // holes lie inside the rings, so they never extend the bounds
+            Shape::Polygon { rings, .. } => Bounds::from_points(rings.iter().flatten()),
+            Shape::Circle { center, radius } => Bounds {
+                min_x: center.0 - radius,
+                min_y: center.1 - radius,
+                max_x: center.0 + radius,
+                max_y: center.1 + radius,
+            },
+            Shape::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } => Bounds {
+                min_x: center.0 - radius_x,
+                min_y: center.1 - radius_y,
+                max_x: center.0 + radius_x,
+                max_y: center.1 + radius_y,
+            },
+            Shape::RoundedRect {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => Bounds {
+                min_x: *x,
+                min_y: *y,
+                max_x: x + width,
+                max_y: y + height,
+            },
+        }
+    }
+}
+
+/// An immutable snapshot of the hit region of a polygon.
+///
+/// Updates replace the snapshot as a whole, so readers never see a shape
+/// together with the bounds or fill rule of another one, and never read freed memory.
+#[derive(Debug)]
+pub struct Geometry {
+    pub shape: Shape,
+    /// Distance to grow (positive) or shrink (negative) the hit region by
+    pub padding: f64,
+    /// How the rings of the shape are filled
+    pub fill_rule: FillRule,
+    /// Cached bounding box of the hit region, padding included
+    pub bounds: Bounds,
+}
+
+impl Geometry {
+    pub fn new(shape: Shape, padding: f64, fill_rule: FillRule) -> Self {
+        Self {
+            bounds: shape.bounds().inflate(padding),
+            shape,
+            padding,
+            fill_rule,
+        }
+    }
+    /// The shape is offset by the padding with round corners,
+    /// i.e. a point is in the hit region if it is within `padding` of the shape,
+    /// or inside the shape and at least `-padding` away from its outline.
+    pub fn contains(&self, point: (f64, f64)) -> bool {
+        if !self.bounds.contains(point) {
+            return false;
+        }
+
+        let inside = self.shape.contains(point, self.fill_rule);
+        if self.padding > 0.0 {
+            inside || self.shape.distance_to_boundary(point) <= self.padding
+        } else if self.padding < 0.0 {
+            inside && self.shape.distance_to_boundary(point) >= -self.padding
+        } else {
+            inside
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Polygon {
     id: PolygonId,
-    /// The points of the polygon, at least 3 points needed
-    points: AtomicPtr<Vec<(AtomicF64, AtomicF64)>>,
+    /// The hit region of the polygon, readers load it without locking
+    geometry: ArcSwap<Geometry>,
+    /// Polygons with a higher z-index are on top of those with a lower one
+    z_index: AtomicI32,
+    /// Creation order, breaks ties between equal z-indexes
+    order: u64,
+    /// Arbitrary data attached by the user, returned with `PolygonInfo`
+    metadata: ArcSwapOption<serde_json::Value>,
     /// Whether the polygon is currently being displayed
     display: AtomicBool,
     /// Whether the cursor is currently in the polygon
@@ -16,48 +503,82 @@ pub struct Polygon {
 }
 
 impl Polygon {
-    pub fn new(id: &str, points: &[(f64, f64)]) -> Self {
-        let points: Vec<(AtomicF64, AtomicF64)> = points
-            .iter()
-            .map(|(x, y)| (AtomicF64::new(*x), AtomicF64::new(*y)))
-            .collect();
+    /// `order` comes from `Registry::next_order`.
+    pub fn new(id: &str, shape: Shape, order: u64) -> Self {
         Self {
             id: id.to_string(),
-            points: AtomicPtr::new(Box::into_raw(Box::new(points))),
+            geometry: ArcSwap::from_pointee(Geometry::new(shape, 0.0, FillRule::default())),
+            z_index: AtomicI32::new(0),
+            order,
+            metadata: ArcSwapOption::empty(),
             display: AtomicBool::new(false),
             cursor_in: AtomicBool::new(false),
         }
     }
-    pub fn default(id: &str) -> Self {
-        Self::new(id, &[(0.0, 0.0), (0.0, 0.0), (0.0, 0.0)])
+    pub fn default(id: &str, order: u64) -> Self {
+        Self::new(
+            id,
+            Shape::Polygon {
+                rings: vec![vec![(0.0, 0.0), (0.0, 0.0), (0.0, 0.0)]],
+                holes: Vec::new(),
+            },
+            order,
+        )
     }
     pub fn id(&self) -> &str {
         &self.id
     }
-    pub fn set_points(&self, points: &[(f64, f64)]) {
-        let new_points: Vec<(AtomicF64, AtomicF64)> = points
-            .iter()
-            .map(|(x, y)| (AtomicF64::new(*x), AtomicF64::new(*y)))
-            .collect();
-
-        let old_points = self.points.load(Ordering::SeqCst);
-        self.points
-            .store(Box::into_raw(Box::new(new_points)), Ordering::SeqCst);
-
-        // Points are saved as raw ptr so we need to free them manually
-        drop(unsafe { Box::from_raw(old_points) });
+    pub fn set_padding(&self, padding: f64) {
+        self.geometry
+            .rcu(|geometry| Geometry::new(geometry.shape.clone(), padding, geometry.fill_rule));
+    }
+    #[cfg(any(test, feature = "test-harness"))]
+    pub fn set_geometry(&self, geometry: Geometry) {
+        self.geometry.store(Arc::new(geometry));
     }
-    pub fn points(&self) -> Vec<(f64, f64)> {
-        unsafe {
-            self.points
-                .load(Ordering::SeqCst)
-                .as_ref()
-                .unwrap()
-                .iter()
-                .map(|(x, y)| (x.load(Ordering::SeqCst), y.load(Ordering::SeqCst)))
-                .collect()
+    /// Replace the geometry by one made from the current snapshot.
+    ///
+    /// If another update lands in between, `f` runs again on its result, so concurrent
+    /// updates are never lost. Nothing is changed if `f` fails.
+    pub fn update_geometry<E>(
+        &self,
+        mut f: impl FnMut(&Geometry) -> Result<Geometry, E>,
+    ) -> Result<(), E> {
+        let mut current = self.geometry.load_full();
+        loop {
+            let next = Arc::new(f(&current)?);
+            let previous = self.geometry.compare_and_swap(&current, next);
+            if Arc::ptr_eq(&previous, &current) {
+                return Ok(());
+            }
+            current = Guard::into_inner(previous);
         }
     }
+    pub fn geometry(&self) -> Arc<Geometry> {
+        self.geometry.load_full()
+    }
+    pub fn bounds(&self) -> Bounds {
+        self.geometry.load().bounds
+    }
+    pub fn fill_rule(&self) -> FillRule {
+        self.geometry.load().fill_rule
+    }
+    pub fn set_z_index(&self, z_index: i32) {
+        self.z_index.store(z_index, Ordering::SeqCst);
+    }
+    pub fn z_index(&self) -> i32 {
+        self.z_index.load(Ordering::SeqCst)
+    }
+    /// Greater priorities are on top.
+    pub fn priority(&self) -> (i32, u64) {
+        (self.z_index(), self.order)
+    }
+    pub fn set_metadata(&self, metadata: Option<serde_json::Value>) {
+        self.metadata.store(metadata.map(Arc::new));
+    }
+    pub fn metadata(&self) -> Option<serde_json::Value> {
+        self.metadata.load().as_deref().cloned()
+    }
     pub fn hide(&self) {
         self.display.store(false, Ordering::SeqCst);
     }
@@ -73,10 +594,53 @@ impl Polygon {
     pub fn display(&self) -> bool {
         self.display.load(Ordering::SeqCst)
     }
-    pub fn distroy(&self) {
-        // Points are saved as raw ptr so we need to free them manually
-        let points = self.points.load(Ordering::SeqCst);
-        self.points.store(std::ptr::null_mut(), Ordering::SeqCst);
-        drop(unsafe { Box::from_raw(points) });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: f64) -> Shape {
+        Shape::Polygon {
+            rings: vec![vec![(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)]],
+            holes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn inflate_ignores_negative_amounts() {
+        let bounds = square(100.0).bounds();
+        assert_eq!(
+            bounds.inflate(5.0),
+            Bounds {
+                min_x: -5.0,
+                min_y: -5.0,
+                max_x: 105.0,
+                max_y: 105.0,
+            }
+        );
+        assert_eq!(bounds.inflate(-5.0), bounds);
+    }
+
+    #[test]
+    fn positive_padding_grows_the_hit_region() {
+        let geometry = Geometry::new(square(100.0), 5.0, FillRule::NonZero);
+
+        assert!(geometry.contains((103.0, 50.0)));
+        assert!(!geometry.contains((106.0, 50.0)));
+        // the corners are round, (-4, -4) is in the bounds but 5.66 away from the square
+        assert!(geometry.contains((-3.0, -3.0)));
+        assert!(geometry.bounds.contains((-4.0, -4.0)));
+        assert!(!geometry.contains((-4.0, -4.0)));
+    }
+
+    #[test]
+    fn negative_padding_shrinks_the_hit_region() {
+        let geometry = Geometry::new(square(100.0), -5.0, FillRule::NonZero);
+
+        assert_eq!(geometry.bounds, square(100.0).bounds());
+        assert!(!geometry.contains((3.0, 50.0)));
+        assert!(geometry.contains((6.0, 50.0)));
+        assert!(!geometry.contains((-1.0, 50.0)));
     }
 }
@@ -0,0 +1,276 @@
+use std::{
+    cmp::Reverse,
+    fs::File,
+    io::{LineWriter, Write},
+    path::Path,
+    sync::{atomic::Ordering, Arc, Mutex},
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::grab::{Clock, Event};
+use crate::input::InputEvent;
+use crate::polygon::{Bounds, FillRule, Polygon, PolygonId, Shape};
+use crate::registry::Registry;
+use crate::state::State;
+use crate::utils::CoordinateMode;
+
+/// A line of a recording, see `Polygon::record`.
+///
+/// Changes of windows, monitors and polygons are written as they are made,
+/// input events as they arrive, followed by what the plugin did about them.
+/// Positions, points and lengths are in physical pixels.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Record {
+    /// The first line, with the cursor state when the recording started
+    Start {
+        default_window: String,
+        mouse: (f64, f64),
+        monitor: Option<usize>,
+    },
+    CoordinateMode {
+        mode: CoordinateMode,
+    },
+    Monitors {
+        monitors: Vec<Bounds>,
+    },
+    Window {
+        label: String,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        scale_factor: f64,
+        host_monitor: Option<usize>,
+    },
+    /// Polygons the cursor was in when the recording started, the topmost first
+    Hover {
+        window: String,
+        ids: Vec<PolygonId>,
+        topmost: Option<PolygonId>,
+        mouse_in_polygon: bool,
+    },
+    /// The polygon was registered or changed, or removed if `polygon` is `None`
+    Polygon {
+        window: String,
+        id: PolygonId,
+        polygon: Option<PolygonState>,
+    },
+    /// `time` is in microseconds since the recording started
+    Input {
+        time: u64,
+        event: InputEvent,
+    },
+    /// An event emitted to the frontend, by its name
+    Emit {
+        event: String,
+        payload: serde_json::Value,
+    },
+    IgnoreCursorEvents {
+        window: String,
+        ignore: bool,
+    },
+}
+
+impl Record {
+    pub(crate) fn emit(event: &Event) -> Self {
+        Record::Emit {
+            event: event.to_string(),
+            payload: event.payload(),
+        }
+    }
+    pub(crate) fn window(registry: &Registry) -> Self {
+        let (x, y) = registry.frame.position();
+        Record::Window {
+            label: registry.label().to_string(),
+            x,
+            y,
+            width: registry.frame.width(),
+            height: registry.frame.height(),
+            scale_factor: registry.frame.scale_factor(),
+            host_monitor: registry.frame.host_monitor(),
+        }
+    }
+    /// `None` if the polygon was removed.
+    pub(crate) fn polygon(registry: &Registry, id: &str, polygon: Option<&Polygon>) -> Self {
+        Record::Polygon {
+            window: registry.label().to_string(),
+            id: id.to_string(),
+            polygon: polygon.map(PolygonState::from),
+        }
+    }
+}
+
+/// What hit testing needs to know about a polygon.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolygonState {
+    pub shape: Shape,
+    pub padding: f64,
+    pub fill_rule: FillRule,
+    pub z_index: i32,
+    pub visible: bool,
+}
+
+impl From<&Polygon> for PolygonState {
+    fn from(polygon: &Polygon) -> Self {
+        let geometry = polygon.geometry();
+        Self {
+            shape: geometry.shape.clone(),
+            padding: geometry.padding,
+            fill_rule: polygon.fill_rule(),
+            z_index: polygon.z_index(),
+            visible: polygon.display(),
+        }
+    }
+}
+
+/// Writes the recording in progress, if any.
+#[derive(Default)]
+pub(crate) struct Recorder {
+    recording: Mutex<Option<Recording>>,
+    /// Why the recording stopped while writing a change, returned by the next `input`
+    failure: Mutex<Option<Error>>,
+}
+
+struct Recording {
+    file: LineWriter<File>,
+    clock: Arc<dyn Clock>,
+    start: Instant,
+}
+
+impl Recorder {
+    /// Start a recording at `path`, replacing the one in progress.
+    pub(crate) fn start(
+        &self,
+        path: &Path,
+        state: &State,
+        default_window: &str,
+        clock: Arc<dyn Clock>,
+    ) -> Result<()> {
+        let file = File::create(path)
+            .map_err(|e| Error::RecordingError(format!("{}: {e}", path.display())))?;
+        let registries = state.registries()?;
+        // polygons are locked before the recording, as where they change,
+        // so a change is either in the snapshot or written after it
+        let polygons = registries
+            .iter()
+            .map(|registry| registry.polygons.read())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let mut current = self.recording.lock()?;
+
+        let mut recording = Recording {
+            file: LineWriter::new(file),
+            start: clock.now(),
+            clock,
+        };
+        let (x, y) = state.mouse.position();
+        recording.write(&Record::Start {
+            default_window: default_window.to_string(),
+            mouse: (x, y),
+            monitor: state.mouse.monitor(),
+        })?;
+        recording.write(&Record::CoordinateMode {
+            mode: state.coordinate_mode(),
+        })?;
+        recording.write(&Record::Monitors {
+            monitors: state.monitors.read()?.clone(),
+        })?;
+        for (registry, polygons) in registries.iter().zip(&polygons) {
+            recording.write(&Record::window(registry))?;
+            let mut sorted = polygons.values().collect::<Vec<&Polygon>>();
+            sorted.sort_by_key(|polygon| polygon.priority().1);
+            for polygon in sorted {
+                recording.write(&Record::polygon(registry, polygon.id(), Some(polygon)))?;
+            }
+        }
+        for (registry, polygons) in registries.iter().zip(&polygons) {
+            let mut ids = registry
+                .hovered
+                .read()?
+                .iter()
+                .cloned()
+                .collect::<Vec<PolygonId>>();
+            ids.sort_by_cached_key(|id| Reverse(polygons.get(id).map(Polygon::priority)));
+            recording.write(&Record::Hover {
+                window: registry.label().to_string(),
+                ids,
+                topmost: registry.topmost.read()?.clone(),
+                mouse_in_polygon: registry.mouse_in_polygon.load(Ordering::SeqCst),
+            })?;
+        }
+
+        *current = Some(recording);
+        *self.failure.lock()? = None;
+        Ok(())
+    }
+    pub(crate) fn stop(&self) -> Result<()> {
+        if let Some(mut recording) = self.recording.lock()?.take() {
+            recording
+                .file
+                .flush()
+                .map_err(|e| Error::RecordingError(e.to_string()))?;
+        }
+        Ok(())
+    }
+    /// Write an input event, after returning a failure to write a change if there was one.
+    pub(crate) fn input(&self, event: &InputEvent) -> Result<()> {
+        if let Some(e) = self.failure.lock()?.take() {
+            return Err(e);
+        }
+        self.with(|recording| {
+            let time = recording
+                .clock
+                .now()
+                .saturating_duration_since(recording.start)
+                .as_micros() as u64;
+            recording.write(&Record::Input {
+                time,
+                event: *event,
+            })
+        })
+    }
+    /// Write what the plugin did, `record` is only called while recording.
+    pub(crate) fn write(&self, record: impl FnOnce() -> Record) -> Result<()> {
+        self.with(|recording| recording.write(&record()))
+    }
+    /// Write a change where it is made, which is often where errors can not be emitted,
+    /// so a failure is kept for the next `input`.
+    ///
+    /// `record` is called under the lock of the recording, so if it reads the current state,
+    /// the last line about a thing describes it as it is, even if it changes concurrently.
+    pub(crate) fn change(&self, record: impl FnOnce() -> Record) {
+        if let Err(e) = self.write(record) {
+            if let Ok(mut failure) = self.failure.lock() {
+                failure.get_or_insert(e);
+            }
+        }
+    }
+    /// The recording is stopped if writing fails, so the failure is reported once.
+    fn with(&self, f: impl FnOnce(&mut Recording) -> Result<()>) -> Result<()> {
+        let mut recording = self.recording.lock()?;
+        let Some(current) = recording.as_mut() else {
+            return Ok(());
+        };
+        let result = f(current);
+        if result.is_err() {
+            *recording = None;
+        }
+        result
+    }
+}
+
+impl Recording {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        let line =
+            serde_json::to_string(record).map_err(|e| Error::RecordingError(e.to_string()))?;
+        writeln!(self.file, "{line}").map_err(|e| Error::RecordingError(e.to_string()))
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use portable_atomic::AtomicF64;
+
+use crate::index::Grid;
+use crate::polygon::{Polygon, PolygonId};
+use crate::record::{Record, Recorder};
+
+/// Polygons of a window, along with the cursor state of the window.
+pub(crate) struct Registry {
+    label: String,
+    pub(crate) polygons: RwLock<HashMap<PolygonId, Polygon>>,
+    /// Spatial index over the bounding boxes of the polygons
+    pub(crate) index: RwLock<Grid>,
+    /// Ids of polygons the cursor is currently in
+    pub(crate) hovered: RwLock<HashSet<PolygonId>>,
+    /// Topmost polygon at the last cursor position, `MouseEnter` is emitted when it changes
+    pub(crate) topmost: RwLock<Option<PolygonId>>,
+    /// Whether the window receives cursor events, i.e. the cursor is in one of its polygons
+    pub(crate) mouse_in_polygon: AtomicBool,
+    pub(crate) frame: Frame,
+    /// Source of `Polygon::order`, so overlapping polygons with the same z-index
+    /// resolve to the one created last
+    next_order: AtomicU64,
+    /// Shared with `State`, polygons are written to it as they change
+    recorder: Arc<Recorder>,
+}
+
+impl Registry {
+    pub(crate) fn new(label: &str, recorder: Arc<Recorder>) -> Self {
+        Self {
+            label: label.to_string(),
+            polygons: RwLock::new(HashMap::new()),
+            index: RwLock::new(Grid::default()),
+            hovered: RwLock::new(HashSet::new()),
+            topmost: RwLock::new(None),
+            mouse_in_polygon: AtomicBool::new(false),
+            frame: Frame::default(),
+            next_order: AtomicU64::new(0),
+            recorder,
+        }
+    }
+    /// Label of the window
+    pub(crate) fn label(&self) -> &str {
+        &self.label
+    }
+    /// Order of a polygon created now, after all existing ones.
+    pub(crate) fn next_order(&self) -> u64 {
+        self.next_order.fetch_add(1, Ordering::SeqCst)
+    }
+    /// Write the polygon to the recording in progress, `None` if it was removed.
+    /// Called while the polygons are locked, so lines follow the order of the changes.
+    pub(crate) fn record(&self, id: &str, polygon: Option<&Polygon>) {
+        self.recorder.change(|| Record::polygon(self, id, polygon));
+    }
+    /// Write the frame of the window to the recording in progress.
+    pub(crate) fn record_frame(&self) {
+        self.recorder.change(|| Record::window(self));
+    }
+}
+
+/// Place of a window on the virtual desktop, in physical pixels.
+#[derive(Debug)]
+pub(crate) struct Frame {
+    /// Outer position of the window
+    x: AtomicF64,
+    y: AtomicF64,
+    width: AtomicF64,
+    height: AtomicF64,
+    /// Physical pixels per logical pixel
+    scale_factor: AtomicF64,
+    /// Index in `State::monitors` of the monitor hosting the window, `-1` if unknown
+    host_monitor: AtomicI64,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self {
+            x: AtomicF64::new(0.0),
+            y: AtomicF64::new(0.0),
+            width: AtomicF64::new(0.0),
+            height: AtomicF64::new(0.0),
+            scale_factor: AtomicF64::new(1.0),
+            host_monitor: AtomicI64::new(-1),
+        }
+    }
+}
+
+impl Frame {
+    /// Outer position of the window on the screen
+    pub(crate) fn position(&self) -> (f64, f64) {
+        (self.x.load(Ordering::SeqCst), self.y.load(Ordering::SeqCst))
+    }
+    pub(crate) fn width(&self) -> f64 {
+        self.width.load(Ordering::SeqCst)
+    }
+    pub(crate) fn height(&self) -> f64 {
+        self.height.load(Ordering::SeqCst)
+    }
+    pub(crate) fn scale_factor(&self) -> f64 {
+        self.scale_factor.load(Ordering::SeqCst)
+    }
+    pub(crate) fn set_position(&self, x: f64, y: f64) {
+        self.x.store(x, Ordering::SeqCst);
+        self.y.store(y, Ordering::SeqCst);
+    }
+    pub(crate) fn set_size(&self, width: f64, height: f64, scale_factor: f64) {
+        self.width.store(width, Ordering::SeqCst);
+        self.height.store(height, Ordering::SeqCst);
+        self.scale_factor.store(scale_factor, Ordering::SeqCst);
+    }
+    pub(crate) fn host_monitor(&self) -> Option<usize> {
+        usize::try_from(self.host_monitor.load(Ordering::SeqCst)).ok()
+    }
+    pub(crate) fn set_host_monitor(&self, monitor: Option<usize>) {
+        self.host_monitor
+            .store(monitor.map_or(-1, |i| i as i64), Ordering::SeqCst);
+    }
+    /// Convert a global position on the screen, as reported by input devices,
+    /// into a position relative to the window. Both in physical pixels.
+    pub(crate) fn screen_to_window(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            x - self.x.load(Ordering::SeqCst),
+            y - self.y.load(Ordering::SeqCst),
+        )
+    }
+    /// Whether polygons of the window can be under a position on the given monitor,
+    /// i.e. it is the monitor hosting the window, or monitors are unknown.
+    pub(crate) fn is_on_host_monitor(&self, monitor: Option<usize>) -> bool {
+        match (
+            usize::try_from(self.host_monitor.load(Ordering::SeqCst)),
+            monitor,
+        ) {
+            (Ok(host), Some(monitor)) => host == monitor,
+            _ => true,
+        }
+    }
+}
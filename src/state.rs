@@ -0,0 +1,170 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicU8, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+
+use portable_atomic::AtomicF64;
+use tauri::{Runtime, Window};
+
+use crate::error::{Error, Result};
+use crate::input::InputBackend;
+use crate::polygon::Bounds;
+use crate::record::{Record, Recorder};
+use crate::registry::{Frame, Registry};
+use crate::utils::{Convert, CoordinateMode};
+
+/// State of a plugin instance, owned by `desktop::Polygon` and shared with its event handlers.
+pub(crate) struct State {
+    /// Registries of the windows polygons belong to, by window label
+    registries: RwLock<BTreeMap<String, Arc<Registry>>>,
+    /// Areas of all monitors on the virtual desktop, in physical pixels
+    pub(crate) monitors: RwLock<Vec<Bounds>>,
+    /// `u8` of the `CoordinateMode` used to exchange points and positions
+    coordinate_mode: AtomicU8,
+    /// Whether to clean up points before validation instead of rejecting them
+    normalize_points: AtomicBool,
+    pub(crate) mouse: Mouse,
+    /// Taken when the first window is ready, a single backend feeds events for all windows
+    pub(crate) backend: Mutex<Option<Box<dyn InputBackend>>>,
+    /// Shared with the registries, which write changes of polygons to it
+    pub(crate) recorder: Arc<Recorder>,
+}
+
+impl State {
+    pub(crate) fn new(backend: Box<dyn InputBackend>) -> Self {
+        Self {
+            registries: RwLock::new(BTreeMap::new()),
+            monitors: RwLock::new(Vec::new()),
+            coordinate_mode: AtomicU8::new(0),
+            normalize_points: AtomicBool::new(false),
+            mouse: Mouse::default(),
+            backend: Mutex::new(Some(backend)),
+            recorder: Arc::default(),
+        }
+    }
+    /// Create the registry of the window, or update it if the window was created again,
+    /// in which case polygons registered before are kept.
+    pub(crate) fn init<R: Runtime>(&self, win: &Window<R>) -> Result<()> {
+        let size = win.outer_size()?;
+
+        let registry = self.insert(win.label())?;
+
+        if let Ok(position) = win.outer_position() {
+            registry
+                .frame
+                .set_position(position.x as f64, position.y as f64);
+        }
+
+        registry.frame.set_size(
+            size.width as f64,
+            size.height as f64,
+            win.scale_factor().unwrap_or(1.0),
+        );
+        registry.record_frame();
+        Ok(())
+    }
+    /// Registry of the window by given label, created if there is none yet.
+    pub(crate) fn insert(&self, label: &str) -> Result<Arc<Registry>> {
+        Ok(self
+            .registries
+            .write()?
+            .entry(label.to_string())
+            .or_insert_with(|| Arc::new(Registry::new(label, self.recorder.clone())))
+            .clone())
+    }
+    /// Registry of the window by given label, created once the window is ready.
+    pub(crate) fn registry(&self, label: &str) -> Result<Arc<Registry>> {
+        self.registries
+            .read()?
+            .get(label)
+            .cloned()
+            .ok_or(Error::NotInitialized)
+    }
+    /// Registries of all windows which are ready.
+    pub(crate) fn registries(&self) -> Result<Vec<Arc<Registry>>> {
+        Ok(self.registries.read()?.values().cloned().collect())
+    }
+    pub(crate) fn coordinate_mode(&self) -> CoordinateMode {
+        self.coordinate_mode.load(Ordering::SeqCst).into()
+    }
+    pub(crate) fn set_coordinate_mode(&self, mode: CoordinateMode) {
+        self.coordinate_mode.store(mode.into(), Ordering::SeqCst);
+        self.recorder.change(|| Record::CoordinateMode {
+            mode: self.coordinate_mode(),
+        });
+    }
+    pub(crate) fn normalize_points(&self) -> bool {
+        self.normalize_points.load(Ordering::SeqCst)
+    }
+    pub(crate) fn set_normalization(&self, enabled: bool) {
+        self.normalize_points.store(enabled, Ordering::SeqCst);
+    }
+    /// Conversion for the window in the given frame, in the current `CoordinateMode`.
+    pub(crate) fn convert(&self, frame: &Frame) -> Convert {
+        Convert::new(frame, self.coordinate_mode())
+    }
+    /// Index of the monitor at a global position in physical pixels,
+    /// in the order of `Window::available_monitors`.
+    pub(crate) fn monitor_at(&self, x: f64, y: f64) -> Option<usize> {
+        let monitors = self.monitors.read().ok()?;
+        // right and bottom edges belong to the next monitor
+        monitors
+            .iter()
+            .position(|m| x >= m.min_x && x < m.max_x && y >= m.min_y && y < m.max_y)
+    }
+    /// Bounding box of all monitors, `None` if monitors are unknown.
+    pub(crate) fn screen(&self) -> Option<Bounds> {
+        let screen = self
+            .monitors
+            .read()
+            .ok()?
+            .iter()
+            .fold(Bounds::EMPTY, |screen, m| Bounds {
+                min_x: screen.min_x.min(m.min_x),
+                min_y: screen.min_y.min(m.min_y),
+                max_x: screen.max_x.max(m.max_x),
+                max_y: screen.max_y.max(m.max_y),
+            });
+        (!screen.is_empty()).then_some(screen)
+    }
+}
+
+/// Last known state of the cursor.
+#[derive(Debug)]
+pub(crate) struct Mouse {
+    /// Saves physical pixel number, relative to the screen
+    x: AtomicF64,
+    y: AtomicF64,
+    /// Index of the monitor the cursor is on, `-1` if unknown
+    monitor: AtomicI64,
+}
+
+impl Default for Mouse {
+    fn default() -> Self {
+        Self {
+            x: AtomicF64::new(0.0),
+            y: AtomicF64::new(0.0),
+            monitor: AtomicI64::new(-1),
+        }
+    }
+}
+
+impl Mouse {
+    pub(crate) fn position(&self) -> (f64, f64) {
+        (self.x.load(Ordering::SeqCst), self.y.load(Ordering::SeqCst))
+    }
+    pub(crate) fn set_position(&self, x: f64, y: f64) {
+        self.x.store(x, Ordering::SeqCst);
+        self.y.store(y, Ordering::SeqCst);
+    }
+    pub(crate) fn monitor(&self) -> Option<usize> {
+        usize::try_from(self.monitor.load(Ordering::SeqCst)).ok()
+    }
+    pub(crate) fn set_monitor(&self, monitor: Option<usize>) {
+        self.monitor
+            .store(monitor.map_or(-1, |i| i as i64), Ordering::SeqCst);
+    }
+}
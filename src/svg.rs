@@ -0,0 +1,615 @@
+use std::f64::consts::PI;
+
+use crate::error::{Error, Result};
+
+/// Default maximum distance between a curve and the segments replacing it, in physical pixels.
+pub const DEFAULT_TOLERANCE: f64 = 0.25;
+
+/// Maximum number of segments a single curve is flattened into.
+const MAX_SEGMENTS: usize = 1024;
+
+/// Parse SVG path data (the `d` attribute) into rings.
+///
+/// Every subpath becomes a ring, Bézier curves and elliptical arcs are flattened into
+/// line segments which deviate from the curve by at most `tolerance`.
+/// Subpaths that can not enclose any area (less than 3 vertices) are dropped.
+/// Curves which need more than 1024 segments to keep within `tolerance` are rejected.
+pub fn flatten(data: &str, tolerance: f64) -> Result<Vec<Vec<(f64, f64)>>> {
+    if !(tolerance.is_finite() && tolerance > 0.0) {
+        return Err(Error::InvalidSvgPath(format!(
+            "Tolerance should be a positive number but got {tolerance}."
+        )));
+    }
+
+    let mut parser = Parser {
+        bytes: data.as_bytes(),
+        pos: 0,
+    };
+    let mut path = Path {
+        tolerance,
+        rings: Vec::new(),
+        ring: Vec::new(),
+        current: (0.0, 0.0),
+        start: (0.0, 0.0),
+    };
+    // control point of the previous curve, used to reflect for `S` and `T`
+    let mut last_control: Option<(char, (f64, f64))> = None;
+
+    let mut command = match parser.command()? {
+        Some(c) if c == 'M' || c == 'm' => c,
+        Some(c) => {
+            return Err(Error::InvalidSvgPath(format!(
+                "Path data should start with a moveto but got [{c}]."
+            )))
+        }
+        None => return Err(Error::InvalidSvgPath("Path data is empty.".into())),
+    };
+
+    loop {
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative { path.current } else { (0.0, 0.0) };
+        let offset = |(x, y): (f64, f64)| (x + origin.0, y + origin.1);
+
+        match command.to_ascii_uppercase() {
+            'M' => {
+                let p = offset(parser.pair()?);
+                path.move_to(p);
+                last_control = None;
+                // subsequent pairs of a moveto are implicit linetos
+                command = if relative { 'l' } else { 'L' };
+                if !parser.has_number() {
+                    command = match parser.command()? {
+                        Some(c) => c,
+                        None => break,
+                    };
+                }
+                continue;
+            }
+            'L' => {
+                let p = offset(parser.pair()?);
+                path.line_to(p);
+                last_control = None;
+            }
+            'H' => {
+                let x = parser.number()? + origin.0;
+                path.line_to((x, path.current.1));
+                last_control = None;
+            }
+            'V' => {
+                let y = parser.number()? + origin.1;
+                path.line_to((path.current.0, y));
+                last_control = None;
+            }
+            'C' => {
+                let c1 = offset(parser.pair()?);
+                let c2 = offset(parser.pair()?);
+                let p = offset(parser.pair()?);
+                path.cubic_to(c1, c2, p)?;
+                last_control = Some(('C', c2));
+            }
+            'S' => {
+                let c1 = reflect(last_control, 'C', path.current);
+                let c2 = offset(parser.pair()?);
+                let p = offset(parser.pair()?);
+                path.cubic_to(c1, c2, p)?;
+                last_control = Some(('C', c2));
+            }
+            'Q' => {
+                let c = offset(parser.pair()?);
+                let p = offset(parser.pair()?);
+                path.quadratic_to(c, p)?;
+                last_control = Some(('Q', c));
+            }
+            'T' => {
+                let c = reflect(last_control, 'Q', path.current);
+                let p = offset(parser.pair()?);
+                path.quadratic_to(c, p)?;
+                last_control = Some(('Q', c));
+            }
+            'A' => {
+                let rx = parser.number()?;
+                let ry = parser.number()?;
+                let rotation = parser.number()?;
+                let large_arc = parser.flag()?;
+                let sweep = parser.flag()?;
+                let p = offset(parser.pair()?);
+                path.arc_to((rx, ry), rotation, large_arc, sweep, p)?;
+                last_control = None;
+            }
+            'Z' => {
+                path.close();
+                last_control = None;
+            }
+            _ => {
+                return Err(Error::InvalidSvgPath(format!(
+                    "Unknown command [{command}] at {}.",
+                    parser.pos
+                )))
+            }
+        }
+
+        // a command letter can be omitted if the same command is repeated,
+        // except for closepath which takes no parameters
+        if command.eq_ignore_ascii_case(&'Z') || !parser.has_number() {
+            command = match parser.command()? {
+                Some(c) => c,
+                None => break,
+            };
+        }
+    }
+
+    path.finish();
+
+    if path.rings.is_empty() {
+        return Err(Error::InvalidSvgPath(
+            "Path data does not enclose any area.".into(),
+        ));
+    }
+
+    Ok(path.rings)
+}
+
+fn reflect(
+    last_control: Option<(char, (f64, f64))>,
+    kind: char,
+    current: (f64, f64),
+) -> (f64, f64) {
+    match last_control {
+        Some((k, (x, y))) if k == kind => (2.0 * current.0 - x, 2.0 * current.1 - y),
+        _ => current,
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_separators(&mut self) {
+        while self.pos < self.bytes.len()
+            && (self.bytes[self.pos].is_ascii_whitespace() || self.bytes[self.pos] == b',')
+        {
+            self.pos += 1;
+        }
+    }
+    fn command(&mut self) -> Result<Option<char>> {
+        self.skip_separators();
+        match self.bytes.get(self.pos) {
+            None => Ok(None),
+            Some(b) if b.is_ascii_alphabetic() => {
+                self.pos += 1;
+                Ok(Some(*b as char))
+            }
+            Some(b) => Err(Error::InvalidSvgPath(format!(
+                "Expected a command but got [{}] at {}.",
+                *b as char, self.pos
+            ))),
+        }
+    }
+    fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        matches!(self.bytes.get(self.pos), Some(b) if b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.'))
+    }
+    fn number(&mut self) -> Result<f64> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |parser: &mut Self| {
+            while parser.pos < parser.bytes.len() && parser.bytes[parser.pos].is_ascii_digit() {
+                parser.pos += 1;
+            }
+        };
+
+        if matches!(self.bytes.get(self.pos), Some(b'-' | b'+')) {
+            self.pos += 1;
+        }
+        digits(self);
+        if self.bytes.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            digits(self);
+        }
+        if matches!(self.bytes.get(self.pos), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(self.bytes.get(self.pos), Some(b'-' | b'+')) {
+                self.pos += 1;
+            }
+            digits(self);
+        }
+
+        std::str::from_utf8(&self.bytes[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .filter(|n| n.is_finite())
+            .ok_or_else(|| Error::InvalidSvgPath(format!("Expected a number at {start}.")))
+    }
+    fn pair(&mut self) -> Result<(f64, f64)> {
+        Ok((self.number()?, self.number()?))
+    }
+    /// Arc flags are a single `0` or `1` and may be written without separators.
+    fn flag(&mut self) -> Result<bool> {
+        self.skip_separators();
+        match self.bytes.get(self.pos) {
+            Some(b'0') => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            _ => Err(Error::InvalidSvgPath(format!(
+                "Expected an arc flag at {}.",
+                self.pos
+            ))),
+        }
+    }
+}
+
+struct Path {
+    tolerance: f64,
+    rings: Vec<Vec<(f64, f64)>>,
+    ring: Vec<(f64, f64)>,
+    current: (f64, f64),
+    start: (f64, f64),
+}
+
+impl Path {
+    fn move_to(&mut self, p: (f64, f64)) {
+        self.finish();
+        self.ring.push(p);
+        self.current = p;
+        self.start = p;
+    }
+    fn line_to(&mut self, p: (f64, f64)) {
+        if self.ring.is_empty() {
+            // drawing right after a closepath starts from the start of the closed subpath
+            self.ring.push(self.current);
+        }
+        if self.ring.last() != Some(&p) {
+            self.ring.push(p);
+        }
+        self.current = p;
+    }
+    fn quadratic_to(&mut self, c: (f64, f64), p: (f64, f64)) -> Result<()> {
+        let p0 = self.current;
+        // Wang's formula for the number of segments
+        let dd = distance((p0.0 - 2.0 * c.0 + p.0, p0.1 - 2.0 * c.1 + p.1));
+        let n = segments((dd / (4.0 * self.tolerance)).sqrt())?;
+
+        for i in 1..n {
+            let t = i as f64 / n as f64;
+            let mt = 1.0 - t;
+            self.line_to((
+                mt * mt * p0.0 + 2.0 * mt * t * c.0 + t * t * p.0,
+                mt * mt * p0.1 + 2.0 * mt * t * c.1 + t * t * p.1,
+            ));
+        }
+        self.line_to(p);
+        Ok(())
+    }
+    fn cubic_to(&mut self, c1: (f64, f64), c2: (f64, f64), p: (f64, f64)) -> Result<()> {
+        let p0 = self.current;
+        // Wang's formula for the number of segments
+        let dd = distance((p0.0 - 2.0 * c1.0 + c2.0, p0.1 - 2.0 * c1.1 + c2.1))
+            .max(distance((c1.0 - 2.0 * c2.0 + p.0, c1.1 - 2.0 * c2.1 + p.1)));
+        let n = segments((3.0 * dd / (4.0 * self.tolerance)).sqrt())?;
+
+        for i in 1..n {
+            let t = i as f64 / n as f64;
+            let mt = 1.0 - t;
+            let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+            self.line_to((
+                a * p0.0 + b * c1.0 + c * c2.0 + d * p.0,
+                a * p0.1 + b * c1.1 + c * c2.1 + d * p.1,
+            ));
+        }
+        self.line_to(p);
+        Ok(())
+    }
+    /// Implementation of the endpoint to center parameterization in the SVG specification.
+    fn arc_to(
+        &mut self,
+        radius: (f64, f64),
+        rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        p: (f64, f64),
+    ) -> Result<()> {
+        let p0 = self.current;
+        let (mut rx, mut ry) = (radius.0.abs(), radius.1.abs());
+        if p0 == p {
+            return Ok(());
+        }
+        if rx == 0.0 || ry == 0.0 {
+            self.line_to(p);
+            return Ok(());
+        }
+
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let dx = (p0.0 - p.0) / 2.0;
+        let dy = (p0.1 - p.1) / 2.0;
+        let x1 = cos * dx + sin * dy;
+        let y1 = -sin * dx + cos * dy;
+
+        // scale radii up if they are too small to reach the end point
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coef = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            coef = -coef;
+        }
+        let cx1 = coef * rx * y1 / ry;
+        let cy1 = -coef * ry * x1 / rx;
+        let cx = cos * cx1 - sin * cy1 + (p0.0 + p.0) / 2.0;
+        let cy = sin * cx1 + cos * cy1 + (p0.1 + p.1) / 2.0;
+
+        let angle =
+            |u: (f64, f64), v: (f64, f64)| (u.0 * v.1 - u.1 * v.0).atan2(u.0 * v.0 + u.1 * v.1);
+        let start = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+        let end = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+        let theta = angle((1.0, 0.0), start);
+        let mut delta = angle(start, end);
+        if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        } else if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        }
+
+        // the largest angle one segment may span without deviating more than tolerance
+        let r = rx.max(ry);
+        let step = if self.tolerance < r {
+            2.0 * (1.0 - self.tolerance / r).acos()
+        } else {
+            PI / 2.0
+        };
+        let n = segments(delta.abs() / step)?;
+
+        for i in 1..n {
+            let t = theta + delta * i as f64 / n as f64;
+            let (sin_t, cos_t) = t.sin_cos();
+            self.line_to((
+                cx + rx * cos_t * cos - ry * sin_t * sin,
+                cy + rx * cos_t * sin + ry * sin_t * cos,
+            ));
+        }
+        self.line_to(p);
+        Ok(())
+    }
+    fn close(&mut self) {
+        self.finish();
+        self.current = self.start;
+    }
+    fn finish(&mut self) {
+        let mut ring = std::mem::take(&mut self.ring);
+        // rings are closed implicitly
+        if ring.len() > 1 && ring.first() == ring.last() {
+            ring.pop();
+        }
+        if ring.len() >= 3 {
+            self.rings.push(ring);
+        }
+    }
+}
+
+fn distance((x, y): (f64, f64)) -> f64 {
+    (x * x + y * y).sqrt()
+}
+
+/// Clamping would break the tolerance, so curves needing too many segments are rejected.
+fn segments(n: f64) -> Result<usize> {
+    if n.is_nan() || n > MAX_SEGMENTS as f64 {
+        return Err(Error::InvalidSvgPath(format!(
+            "A curve needs more than {MAX_SEGMENTS} segments to keep within the tolerance."
+        )));
+    }
+    Ok((n.ceil() as usize).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rings(data: &str) -> Vec<Vec<(f64, f64)>> {
+        flatten(data, DEFAULT_TOLERANCE).unwrap()
+    }
+
+    /// Every vertex is on the curve, and no segment strays from it by more than the tolerance.
+    fn assert_follows(ring: &[(f64, f64)], curve: impl Fn(f64) -> (f64, f64), tolerance: f64) {
+        let samples = (0..=20_000)
+            .map(|i| curve(i as f64 / 20_000.0))
+            .collect::<Vec<(f64, f64)>>();
+        let off = |p: (f64, f64)| {
+            samples
+                .iter()
+                .map(|s| distance((s.0 - p.0, s.1 - p.1)))
+                .fold(f64::INFINITY, f64::min)
+        };
+        for w in ring.windows(2) {
+            assert!(off(w[0]) < 0.01, "{:?} is not on the curve", w[0]);
+            let middle = ((w[0].0 + w[1].0) / 2.0, (w[0].1 + w[1].1) / 2.0);
+            assert!(
+                off(middle) <= tolerance + 0.01,
+                "{middle:?} strays from the curve"
+            );
+        }
+    }
+
+    #[test]
+    fn lines() {
+        let square = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        // repeated commands can omit the letter, pairs after a moveto are linetos
+        assert_eq!(rings("M0 0 L10 0 L10 10 L0 10 Z"), vec![square.clone()]);
+        assert_eq!(rings("M0,0 10,0 10,10 0,10z"), vec![square.clone()]);
+        assert_eq!(rings("M0 0 H10 V10 H0 Z"), vec![square.clone()]);
+        assert_eq!(rings("m0 0 10 0 0 10 -10 0 z"), vec![square.clone()]);
+        assert_eq!(rings("M0 0 h10 v10 h-10 z"), [square]);
+        // numbers may be packed without separators
+        assert_eq!(
+            rings("M0,0L10-5 .5.5"),
+            [vec![(0.0, 0.0), (10.0, -5.0), (0.5, 0.5)]]
+        );
+    }
+
+    #[test]
+    fn subpaths() {
+        // relative coordinates after a closepath start from the start of the closed subpath
+        assert_eq!(
+            rings("M0 0 h10 v10 z m20 0 h10 v10 z M40 0 h1"),
+            [
+                vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)],
+                vec![(20.0, 0.0), (30.0, 0.0), (30.0, 10.0)],
+            ]
+        );
+        // drawing right after a closepath starts a new ring at the same point
+        assert_eq!(
+            rings("M0 0 h10 v10 z l-10 0 0 -10"),
+            [
+                vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)],
+                vec![(0.0, 0.0), (-10.0, 0.0), (-10.0, -10.0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn curves() {
+        let ring = &rings("M0 0 C0 10 10 10 10 0")[0];
+        assert!(ring.len() > 3);
+        assert_eq!(ring.first(), Some(&(0.0, 0.0)));
+        assert_eq!(ring.last(), Some(&(10.0, 0.0)));
+        assert_follows(
+            ring,
+            |t| {
+                let mt = 1.0 - t;
+                (
+                    3.0 * mt * t * t * 10.0 + t * t * t * 10.0,
+                    3.0 * mt * t * 10.0,
+                )
+            },
+            DEFAULT_TOLERANCE,
+        );
+
+        let ring = &rings("M0 0 Q5 10 10 0")[0];
+        assert_follows(
+            ring,
+            |t| (10.0 * t, 20.0 * (1.0 - t) * t),
+            DEFAULT_TOLERANCE,
+        );
+    }
+
+    #[test]
+    fn smooth_curves_reflect_the_last_control_point() {
+        assert_eq!(
+            rings("M0 0 C0 10 10 10 10 0 S20 -10 20 0"),
+            rings("M0 0 C0 10 10 10 10 0 C10 -10 20 -10 20 0")
+        );
+        assert_eq!(
+            rings("M0 0 q5 10 10 0 t10 0"),
+            rings("M0 0 Q5 10 10 0 Q15 -10 20 0")
+        );
+        // without a curve of the same kind before, the current point is the control point
+        assert_eq!(rings("M0 0 S10 10 10 0 z"), rings("M0 0 C0 0 10 10 10 0 z"));
+        assert_eq!(
+            rings("M0 0 Q5 10 10 0 S20 -10 20 0"),
+            rings("M0 0 Q5 10 10 0 C10 0 20 -10 20 0")
+        );
+    }
+
+    #[test]
+    fn arcs() {
+        // flags may be packed with the next number
+        let packed = rings("M0 0 a5 5 0 0110 0");
+        assert_eq!(packed, rings("M0 0 a 5 5 0 0 1 10 0"));
+
+        // a half circle around (5, 0), swept in the positive angle direction, i.e. upwards
+        let ring = &packed[0];
+        assert_eq!(ring.last(), Some(&(10.0, 0.0)));
+        assert!(ring[1..ring.len() - 1].iter().all(|p| p.1 < 0.0));
+        assert_follows(
+            ring,
+            |t| {
+                let (sin, cos) = (PI + PI * t).sin_cos();
+                (5.0 + 5.0 * cos, 5.0 * sin)
+            },
+            DEFAULT_TOLERANCE,
+        );
+
+        // radii too small to reach the end point are scaled up
+        assert_eq!(rings("M0 0 A1 1 0 0 1 10 0"), packed);
+        // the other way around
+        assert!(rings("M0 0 A5 5 0 0 0 10 0")[0][1..]
+            .iter()
+            .all(|p| p.1 >= 0.0));
+    }
+
+    #[test]
+    fn rotated_and_large_arcs() {
+        // three quarters of an ellipse with radii 10 and 5, rotated by 90 degrees
+        let ring = &rings("M0 -10 A10 5 90 1 1 -5 0")[0];
+        assert_follows(
+            ring,
+            |t| {
+                let (sin, cos) = (-PI / 2.0 + 1.5 * PI * t).sin_cos();
+                (5.0 * cos, 10.0 * sin)
+            },
+            DEFAULT_TOLERANCE,
+        );
+    }
+
+    #[test]
+    fn tolerance() {
+        let coarse = &rings("M0 0 a50 50 0 1 1 0 100 a50 50 0 1 1 0 -100")[0];
+        let fine = &flatten("M0 0 a50 50 0 1 1 0 100 a50 50 0 1 1 0 -100", 0.01).unwrap()[0];
+        assert!(fine.len() > coarse.len());
+
+        let circle = |t: f64| {
+            let (sin, cos) = (2.0 * PI * t).sin_cos();
+            (50.0 * cos, 50.0 + 50.0 * sin)
+        };
+        assert_follows(coarse, circle, DEFAULT_TOLERANCE);
+        assert_follows(fine, circle, 0.01);
+
+        for tolerance in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(flatten("M0 0 h1 v1 z", tolerance).is_err());
+        }
+
+        // capping the number of segments would stray from the curve, so they are rejected
+        for (data, tolerance) in [
+            ("M0 0 a50 50 0 1 1 0 100 z", 1e-6),
+            ("M0 0 C0 1000 1000 1000 1000 0 z", 1e-4),
+            ("M0 0 Q500 1000 1000 0 z", 1e-4),
+        ] {
+            assert!(
+                matches!(flatten(data, tolerance), Err(Error::InvalidSvgPath(_))),
+                "{data:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_paths() {
+        for data in [
+            "",
+            "   ",
+            "L0 0 10 0 10 10",
+            "M0 0 h10 X10 10",
+            "M0 0 L10",
+            "M0 0 a5 5 0 2 1 10 0",
+            "M0 0 L10 0 z",
+            "M0 0 h10 v10 z 7",
+        ] {
+            assert!(
+                matches!(
+                    flatten(data, DEFAULT_TOLERANCE),
+                    Err(Error::InvalidSvgPath(_))
+                ),
+                "{data:?} should be rejected"
+            );
+        }
+    }
+}
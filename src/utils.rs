@@ -1,44 +1,168 @@
-use std::sync::atomic::Ordering;
+use serde::{Deserialize, Serialize};
 
-use crate::statics::PHYSICAL_WIDTH;
+use crate::polygon::{FillRule, Shape, Transform};
+use crate::registry::Frame;
 
-/// All point data conversions are based on the physical pixel width of the screen
-/// to provide a unified standard.
-/// Therefore, when the front end receives the data,
-/// it needs to be multiplied by window.screen.width to obtain the logical pixel points.
-pub struct Convert {}
-
-impl Convert {
-    pub fn from_viewport(x: f64, y: f64) -> (f64, f64) {
-        let physical_width = get_physical_width();
+/// Units of points, lengths and positions exchanged with the plugin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CoordinateMode {
+    /// Both x and y are fractions of the window width, so `(0.1, 0.1)` is a square.
+    #[default]
+    WidthNormalized,
+    /// x is a fraction of the window width and y of the window height,
+    /// so `(1.0, 1.0)` is the bottom-right corner. Lengths are fractions of the width.
+    Fraction,
+    /// Logical (CSS) pixels.
+    Logical,
+    /// Physical pixels, as the polygons are saved.
+    Physical,
+}
 
-        (x * physical_width, y * physical_width)
+impl From<u8> for CoordinateMode {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => CoordinateMode::Fraction,
+            2 => CoordinateMode::Logical,
+            3 => CoordinateMode::Physical,
+            _ => CoordinateMode::WidthNormalized,
+        }
     }
-    pub fn to_viewport(x: f64, y: f64) -> (f64, f64) {
-        let physical_width = get_physical_width();
+}
 
-        let mut x = x / physical_width;
-        if x < 0.0 {
-            x = 0.0;
-        }
-        if x > 1.0 {
-            x = 1.0;
+impl From<CoordinateMode> for u8 {
+    fn from(value: CoordinateMode) -> Self {
+        match value {
+            CoordinateMode::WidthNormalized => 0,
+            CoordinateMode::Fraction => 1,
+            CoordinateMode::Logical => 2,
+            CoordinateMode::Physical => 3,
         }
+    }
+}
 
-        let mut y = y / physical_width;
-        if y < 0.0 {
-            y = 0.0;
-        }
-        if y > 1.0 {
-            y = 1.0;
+/// Converts between the units of the current `CoordinateMode` (the "viewport")
+/// and the physical pixels polygons are saved in.
+/// Both are relative to the top-left corner of a window, not of the screen.
+pub struct Convert {
+    /// Physical pixels per viewport unit, along x and y
+    scale: (f64, f64),
+}
+
+impl Convert {
+    /// Conversion for the window in the given frame.
+    pub(crate) fn new(frame: &Frame, mode: CoordinateMode) -> Self {
+        let scale = match mode {
+            CoordinateMode::WidthNormalized => (frame.width(), frame.width()),
+            CoordinateMode::Fraction => (frame.width(), frame.height()),
+            CoordinateMode::Logical => (frame.scale_factor(), frame.scale_factor()),
+            CoordinateMode::Physical => (1.0, 1.0),
+        };
+        Self { scale }
+    }
+    pub fn point_from_viewport(&self, x: f64, y: f64) -> (f64, f64) {
+        let (sx, sy) = self.scale;
+
+        (x * sx, y * sy)
+    }
+    pub fn ring_from_viewport(&self, ring: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        ring.iter()
+            .map(|(x, y)| self.point_from_viewport(*x, *y))
+            .collect()
+    }
+    /// Lengths are scaled along x, if the axes are scaled differently.
+    pub fn length_from_viewport(&self, length: f64) -> f64 {
+        length * self.scale.0
+    }
+    pub fn transform_from_viewport(&self, t: &Transform) -> Transform {
+        let (sx, sy) = self.scale;
+        // conjugate by the scaling, which keeps the linear part when both axes are scaled alike
+        Transform {
+            a: t.a,
+            b: t.b * sy / sx,
+            c: t.c * sx / sy,
+            d: t.d,
+            e: t.e * sx,
+            f: t.f * sy,
         }
+    }
+    /// Rotation about `origin` in physical pixels, as conjugating `Transform::rotate`
+    /// by axes scaled differently would shear it.
+    pub fn rotation_from_viewport(&self, degrees: f64, origin: (f64, f64)) -> Transform {
+        Transform::rotate(degrees, self.point_from_viewport(origin.0, origin.1))
+    }
+    pub fn length_to_viewport(&self, length: f64) -> f64 {
+        length / self.scale.0
+    }
+    /// The inverse of how `Polygon::update_*` convert their arguments, so the shape keeps its kind
+    /// and can be passed back as is, even if the axes are scaled differently.
+    pub fn shape_to_viewport(&self, shape: &Shape) -> Shape {
+        map_shape(
+            shape,
+            |x, y| self.to_viewport(x, y),
+            |length| self.length_to_viewport(length),
+        )
+    }
+    /// Convert a shape the way `Polygon::update_*` convert their arguments.
+    pub fn shape_from_viewport(&self, shape: &Shape) -> Shape {
+        map_shape(
+            shape,
+            |x, y| self.point_from_viewport(x, y),
+            |length| self.length_from_viewport(length),
+        )
+    }
+    /// Positions out of the window are kept, e.g. negative ones left of it.
+    pub fn to_viewport(&self, x: f64, y: f64) -> (f64, f64) {
+        let (sx, sy) = self.scale;
 
-        (x, y)
+        (x / sx, y / sy)
     }
 }
 
-pub fn get_physical_width() -> f64 {
-    PHYSICAL_WIDTH.load(Ordering::SeqCst)
+/// Map the points of a shape through `point` and its radii through `length`.
+/// Sizes of rounded rects are mapped as points, like `Polygon::update_rounded_rect` does.
+fn map_shape(
+    shape: &Shape,
+    point: impl Fn(f64, f64) -> (f64, f64),
+    length: impl Fn(f64) -> f64,
+) -> Shape {
+    let map_ring = |ring: &Vec<(f64, f64)>| ring.iter().map(|(x, y)| point(*x, *y)).collect();
+    match shape {
+        Shape::Polygon { rings, holes } => Shape::Polygon {
+            rings: rings.iter().map(map_ring).collect(),
+            holes: holes.iter().map(map_ring).collect(),
+        },
+        Shape::Circle { center, radius } => Shape::Circle {
+            center: point(center.0, center.1),
+            radius: length(*radius),
+        },
+        Shape::Ellipse {
+            center,
+            radius_x,
+            radius_y,
+        } => Shape::Ellipse {
+            center: point(center.0, center.1),
+            radius_x: length(*radius_x),
+            radius_y: length(*radius_y),
+        },
+        Shape::RoundedRect {
+            x,
+            y,
+            width,
+            height,
+            radius,
+        } => {
+            let (x, y) = point(*x, *y);
+            let (width, height) = point(*width, *height);
+            Shape::RoundedRect {
+                x,
+                y,
+                width,
+                height,
+                radius: length(*radius),
+            }
+        }
+    }
 }
 
 pub fn is_point_in_polygon(polygon: &[(f64, f64)], point: (f64, f64)) -> bool {
@@ -65,3 +189,550 @@ pub fn is_point_in_polygon(polygon: &[(f64, f64)], point: (f64, f64)) -> bool {
 
     inside
 }
+
+/// Winding number of the polygon around the point.
+/// The sign depends on the orientation of the polygon, only zero means outside.
+pub fn winding_number(polygon: &[(f64, f64)], point: (f64, f64)) -> i32 {
+    let mut winding = 0;
+    let n = polygon.len();
+
+    for i in 0..n {
+        let (xi, yi) = polygon[i];
+        let (xj, yj) = polygon[(i + 1) % n];
+
+        // > 0 if the point is on the left of the edge, < 0 if on the right
+        let side = (xj - xi) * (point.1 - yi) - (point.0 - xi) * (yj - yi);
+
+        if yi <= point.1 {
+            if yj > point.1 && side > 0.0 {
+                winding += 1;
+            }
+        } else if yj <= point.1 && side < 0.0 {
+            winding -= 1;
+        }
+    }
+
+    winding
+}
+
+/// Rings are combined as a whole by the fill rule,
+/// so disjoint rings behave like a union whichever rule is used.
+pub fn is_point_in_rings(
+    rings: &[Vec<(f64, f64)>],
+    point: (f64, f64),
+    fill_rule: FillRule,
+) -> bool {
+    match fill_rule {
+        FillRule::EvenOdd => {
+            rings
+                .iter()
+                .filter(|ring| is_point_in_polygon(ring, point))
+                .count()
+                % 2
+                == 1
+        }
+        FillRule::NonZero => {
+            rings
+                .iter()
+                .map(|ring| winding_number(ring, point))
+                .sum::<i32>()
+                != 0
+        }
+    }
+}
+
+pub fn is_point_in_circle(center: (f64, f64), radius: f64, point: (f64, f64)) -> bool {
+    let dx = point.0 - center.0;
+    let dy = point.1 - center.1;
+
+    dx * dx + dy * dy <= radius * radius
+}
+
+pub fn is_point_in_ellipse(
+    center: (f64, f64),
+    radius_x: f64,
+    radius_y: f64,
+    point: (f64, f64),
+) -> bool {
+    if radius_x <= 0.0 || radius_y <= 0.0 {
+        return false;
+    }
+
+    let dx = (point.0 - center.0) / radius_x;
+    let dy = (point.1 - center.1) / radius_y;
+
+    dx * dx + dy * dy <= 1.0
+}
+
+/// `rect` is `(x, y, width, height)` with `(x, y)` the top-left corner.
+/// The corner radius is clamped to half of the shorter side.
+pub fn is_point_in_rounded_rect(
+    rect: (f64, f64, f64, f64),
+    radius: f64,
+    point: (f64, f64),
+) -> bool {
+    let (x, y, width, height) = rect;
+    if point.0 < x || point.0 > x + width || point.1 < y || point.1 > y + height {
+        return false;
+    }
+
+    let radius = radius.max(0.0).min(width.min(height) / 2.0);
+
+    // only the four corner squares need an extra check against the corner circle
+    let corner_x = if point.0 < x + radius {
+        x + radius
+    } else if point.0 > x + width - radius {
+        x + width - radius
+    } else {
+        return true;
+    };
+    let corner_y = if point.1 < y + radius {
+        y + radius
+    } else if point.1 > y + height - radius {
+        y + height - radius
+    } else {
+        return true;
+    };
+
+    is_point_in_circle((corner_x, corner_y), radius, point)
+}
+
+fn distance_to_segment(a: (f64, f64), b: (f64, f64), point: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length > 0.0 {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    (point.0 - (a.0 + t * dx)).hypot(point.1 - (a.1 + t * dy))
+}
+
+/// Distance from the point to the nearest edge of all rings.
+pub fn distance_to_rings<'a, I: IntoIterator<Item = &'a Vec<(f64, f64)>>>(
+    rings: I,
+    point: (f64, f64),
+) -> f64 {
+    let mut distance = f64::INFINITY;
+
+    for ring in rings {
+        let n = ring.len();
+        for i in 0..n {
+            distance = distance.min(distance_to_segment(ring[i], ring[(i + 1) % n], point));
+        }
+    }
+
+    distance
+}
+
+pub fn distance_to_circle(center: (f64, f64), radius: f64, point: (f64, f64)) -> f64 {
+    ((point.0 - center.0).hypot(point.1 - center.1) - radius).abs()
+}
+
+/// Exact distance to an axis-aligned ellipse, found by bisection.
+/// See David Eberly, "Distance from a Point to an Ellipse, an Ellipsoid, or a Hyperellipsoid".
+pub fn distance_to_ellipse(
+    center: (f64, f64),
+    radius_x: f64,
+    radius_y: f64,
+    point: (f64, f64),
+) -> f64 {
+    // the ellipse is symmetric, so work in the first quadrant with the major axis along x
+    let (dx, dy) = ((point.0 - center.0).abs(), (point.1 - center.1).abs());
+    let (e0, e1, y0, y1) = if radius_x >= radius_y {
+        (radius_x, radius_y, dx, dy)
+    } else {
+        (radius_y, radius_x, dy, dx)
+    };
+
+    if e1 <= 0.0 {
+        // degenerates to a segment on the major axis
+        return distance_to_segment((-e0, 0.0), (e0, 0.0), (y0, y1));
+    }
+
+    if y1 > 0.0 {
+        if y0 > 0.0 {
+            let z0 = y0 / e0;
+            let z1 = y1 / e1;
+            let g = z0 * z0 + z1 * z1 - 1.0;
+            if g == 0.0 {
+                return 0.0;
+            }
+
+            let r0 = (e0 / e1) * (e0 / e1);
+            let n0 = r0 * z0;
+            let mut s0 = z1 - 1.0;
+            let mut s1 = if g < 0.0 { 0.0 } else { n0.hypot(z1) - 1.0 };
+            let mut s = 0.0;
+            for _ in 0..128 {
+                s = (s0 + s1) / 2.0;
+                if s == s0 || s == s1 {
+                    break;
+                }
+                let ratio0 = n0 / (s + r0);
+                let ratio1 = z1 / (s + 1.0);
+                let g = ratio0 * ratio0 + ratio1 * ratio1 - 1.0;
+                if g > 0.0 {
+                    s0 = s;
+                } else if g < 0.0 {
+                    s1 = s;
+                } else {
+                    break;
+                }
+            }
+
+            let x0 = r0 * y0 / (s + r0);
+            let x1 = y1 / (s + 1.0);
+            (x0 - y0).hypot(x1 - y1)
+        } else {
+            (y1 - e1).abs()
+        }
+    } else {
+        let numerator = e0 * y0;
+        let denominator = e0 * e0 - e1 * e1;
+        if numerator < denominator {
+            let xde0 = numerator / denominator;
+            let x0 = e0 * xde0;
+            let x1 = e1 * (1.0 - xde0 * xde0).sqrt();
+            (x0 - y0).hypot(x1)
+        } else {
+            (y0 - e0).abs()
+        }
+    }
+}
+
+/// `rect` is `(x, y, width, height)` with `(x, y)` the top-left corner.
+pub fn distance_to_rounded_rect(rect: (f64, f64, f64, f64), radius: f64, point: (f64, f64)) -> f64 {
+    let (x, y, width, height) = rect;
+    let radius = radius.max(0.0).min(width.min(height) / 2.0);
+    let half = (width / 2.0 - radius, height / 2.0 - radius);
+
+    // signed distance to a rectangle shrunk by the radius, then rounded by it
+    let qx = (point.0 - (x + width / 2.0)).abs() - half.0;
+    let qy = (point.1 - (y + height / 2.0)).abs() - half.1;
+    let outside = qx.max(0.0).hypot(qy.max(0.0));
+    let inside = qx.max(qy).min(0.0);
+
+    (outside + inside - radius).abs()
+}
+
+/// Maximum distance between a flattened outline and the curve, in physical pixels
+const FLATTEN_TOLERANCE: f64 = 0.1;
+
+/// Number of segments needed to flatten a full turn of radius `radius`.
+fn arc_segments(radius: f64) -> usize {
+    let step = 2.0 * (1.0 - FLATTEN_TOLERANCE / radius).clamp(-1.0, 1.0).acos();
+    if step > 0.0 {
+        ((std::f64::consts::TAU / step).ceil() as usize).clamp(16, 4096)
+    } else {
+        4096
+    }
+}
+
+pub fn ellipse_to_ring(center: (f64, f64), radius_x: f64, radius_y: f64) -> Vec<(f64, f64)> {
+    let n = arc_segments(radius_x.max(radius_y));
+    (0..n)
+        .map(|i| {
+            let (sin, cos) = (std::f64::consts::TAU * i as f64 / n as f64).sin_cos();
+            (center.0 + radius_x * cos, center.1 + radius_y * sin)
+        })
+        .collect()
+}
+
+/// `rect` is `(x, y, width, height)` with `(x, y)` the top-left corner.
+pub fn rounded_rect_to_ring(rect: (f64, f64, f64, f64), radius: f64) -> Vec<(f64, f64)> {
+    let (x, y, width, height) = rect;
+    let radius = radius.max(0.0).min(width.min(height) / 2.0);
+    let n = if radius > 0.0 {
+        arc_segments(radius).div_ceil(4)
+    } else {
+        0
+    };
+
+    // corner arc centers, clockwise on screen starting from the bottom-right
+    let corners = [
+        (x + width - radius, y + height - radius),
+        (x + radius, y + height - radius),
+        (x + radius, y + radius),
+        (x + width - radius, y + radius),
+    ];
+
+    let mut ring = Vec::with_capacity(4 * (n + 1));
+    for (quarter, (cx, cy)) in corners.into_iter().enumerate() {
+        for i in 0..=n {
+            let angle = std::f64::consts::FRAC_PI_2 * (quarter as f64 + i as f64 / n.max(1) as f64);
+            let (sin, cos) = angle.sin_cos();
+            ring.push((cx + radius * cos, cy + radius * sin));
+        }
+    }
+
+    // arcs meet without a straight edge between them if the radius is half of a side
+    let close = |a: &(f64, f64), b: &(f64, f64)| (a.0 - b.0).hypot(a.1 - b.1) < 1e-9;
+    ring.dedup_by(|a, b| close(a, b));
+    while ring.len() > 1 && close(&ring[0], &ring[ring.len() - 1]) {
+        ring.pop();
+    }
+    ring
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes_keep_their_kind() {
+        let frame = Frame::default();
+        frame.set_size(1920.0, 1080.0, 1.5);
+
+        for mode in [
+            CoordinateMode::WidthNormalized,
+            CoordinateMode::Fraction,
+            CoordinateMode::Logical,
+            CoordinateMode::Physical,
+        ] {
+            let convert = Convert::new(&frame, mode);
+            // as `Polygon::update_circle` and `Polygon::update_rounded_rect` store them
+            let (sx, sy) = convert.point_from_viewport(1.0, 1.0);
+            let circle = Shape::Circle {
+                center: (0.5 * sx, 0.25 * sy),
+                radius: 0.125 * sx,
+            };
+            let rect = Shape::RoundedRect {
+                x: 0.25 * sx,
+                y: 0.5 * sy,
+                width: 0.125 * sx,
+                height: 0.25 * sy,
+                radius: 0.0625 * sx,
+            };
+
+            assert_eq!(
+                convert.shape_to_viewport(&circle),
+                Shape::Circle {
+                    center: (0.5, 0.25),
+                    radius: 0.125,
+                },
+                "{mode:?}"
+            );
+            assert_eq!(
+                convert.shape_to_viewport(&rect),
+                Shape::RoundedRect {
+                    x: 0.25,
+                    y: 0.5,
+                    width: 0.125,
+                    height: 0.25,
+                    radius: 0.0625,
+                },
+                "{mode:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rotations_are_not_sheared() {
+        let frame = Frame::default();
+        frame.set_size(2000.0, 1000.0, 1.0);
+        let convert = Convert::new(&frame, CoordinateMode::Fraction);
+
+        let rotation = convert.rotation_from_viewport(90.0, (0.5, 0.5));
+        let (x, y) = rotation.apply((1500.0, 500.0));
+        assert!((x - 1000.0).abs() < 1e-9 && (y - 1000.0).abs() < 1e-9);
+
+        // conjugating the rotation by the axes squashes it, the point ends up 250 pixels away instead of 500
+        let sheared = convert.transform_from_viewport(&Transform::rotate(90.0, (0.5, 0.5)));
+        let (x, y) = sheared.apply((1500.0, 500.0));
+        assert!((x - 1000.0).abs() < 1e-9 && (y - 750.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn circle_containment() {
+        // (3, 4) is exactly on the circle of radius 5
+        assert!(is_point_in_circle((10.0, 10.0), 5.0, (13.0, 14.0)));
+        assert!(is_point_in_circle((10.0, 10.0), 5.0, (10.0, 10.0)));
+        assert!(!is_point_in_circle((10.0, 10.0), 5.0, (13.1, 14.0)));
+    }
+
+    #[test]
+    fn ellipse_containment() {
+        let (center, rx, ry) = ((0.0, 0.0), 4.0, 2.0);
+        for point in [(4.0, 0.0), (-4.0, 0.0), (0.0, 2.0), (2.0, 1.0), (0.0, 0.0)] {
+            assert!(is_point_in_ellipse(center, rx, ry, point), "{point:?}");
+        }
+        // (3, 1.5) is in the bounding box, but out of the ellipse
+        for point in [(3.0, 1.5), (4.1, 0.0), (0.0, -2.1)] {
+            assert!(!is_point_in_ellipse(center, rx, ry, point), "{point:?}");
+        }
+        assert!(!is_point_in_ellipse(center, 4.0, 0.0, (0.0, 0.0)));
+    }
+
+    #[test]
+    fn rounded_rect_containment() {
+        let rect = (0.0, 0.0, 100.0, 50.0);
+        for point in [(50.0, 25.0), (0.0, 25.0), (50.0, 50.0), (100.0, 40.0)] {
+            assert!(is_point_in_rounded_rect(rect, 10.0, point), "{point:?}");
+        }
+        for point in [(-0.1, 25.0), (50.0, 50.1), (101.0, 25.0)] {
+            assert!(!is_point_in_rounded_rect(rect, 10.0, point), "{point:?}");
+        }
+
+        // corners are cut along circles around (10, 10), (90, 10), (10, 40) and (90, 40)
+        assert!(is_point_in_rounded_rect(rect, 10.0, (4.0, 2.0)));
+        assert!(is_point_in_rounded_rect(rect, 10.0, (96.0, 48.0)));
+        for point in [
+            (0.0, 0.0),
+            (2.0, 2.0),
+            (100.0, 0.0),
+            (0.0, 50.0),
+            (98.0, 48.0),
+        ] {
+            assert!(!is_point_in_rounded_rect(rect, 10.0, point), "{point:?}");
+        }
+        // the radius is clamped to half of the height, which leaves a stadium
+        assert!(!is_point_in_rounded_rect(rect, 100.0, (5.0, 5.0)));
+        assert!(is_point_in_rounded_rect(rect, 100.0, (0.0, 25.0)));
+        assert!(is_point_in_rounded_rect(rect, 100.0, (25.0, 0.0)));
+        // without a radius, corners are kept
+        assert!(is_point_in_rounded_rect(rect, 0.0, (0.0, 0.0)));
+    }
+
+    /// Distance to the nearest of many points on the ellipse.
+    fn sampled_distance_to_ellipse(
+        center: (f64, f64),
+        radius_x: f64,
+        radius_y: f64,
+        point: (f64, f64),
+    ) -> f64 {
+        let samples = 200_000;
+        (0..samples)
+            .map(|i| {
+                let angle = i as f64 / samples as f64 * std::f64::consts::TAU;
+                let (x, y) = (
+                    center.0 + radius_x * angle.cos(),
+                    center.1 + radius_y * angle.sin(),
+                );
+                (point.0 - x).hypot(point.1 - y)
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn ellipse_distance_matches_sampling() {
+        let center = (10.0, -20.0);
+        // wide ones, and tall ones where the axes are swapped
+        for (radius_x, radius_y) in [(100.0, 40.0), (40.0, 100.0), (50.0, 50.0)] {
+            for (dx, dy) in [
+                (0.0, 0.0),
+                (30.0, 10.0),
+                (-80.0, 35.0),
+                (150.0, -120.0),
+                (0.0, 70.0),
+                (-90.0, 0.0),
+                (20.0, 0.0),
+                (0.0, -10.0),
+                (radius_x, 0.0),
+            ] {
+                let point = (center.0 + dx, center.1 + dy);
+                let expected = sampled_distance_to_ellipse(center, radius_x, radius_y, point);
+                let distance = distance_to_ellipse(center, radius_x, radius_y, point);
+                assert!(
+                    (distance - expected).abs() < 0.01,
+                    "({radius_x}, {radius_y}) at {point:?}: {distance} != {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn flat_ellipses_are_segments() {
+        // the minor radius is zero, so e1 <= 0 whichever axis is the major one
+        assert_eq!(distance_to_ellipse((0.0, 0.0), 10.0, 0.0, (5.0, 3.0)), 3.0);
+        assert_eq!(distance_to_ellipse((0.0, 0.0), 10.0, 0.0, (13.0, 4.0)), 5.0);
+        assert_eq!(
+            distance_to_ellipse((0.0, 0.0), 0.0, 10.0, (-4.0, -13.0)),
+            5.0
+        );
+    }
+
+    #[test]
+    fn rounded_rect_distance() {
+        let rect = (0.0, 0.0, 100.0, 50.0);
+        // to the nearest edge, from inside and outside
+        assert_eq!(distance_to_rounded_rect(rect, 10.0, (50.0, 25.0)), 25.0);
+        assert_eq!(distance_to_rounded_rect(rect, 10.0, (50.0, -5.0)), 5.0);
+        assert_eq!(distance_to_rounded_rect(rect, 10.0, (103.0, 25.0)), 3.0);
+        // to the corner arc around (10, 10)
+        assert!(distance_to_rounded_rect(rect, 10.0, (4.0, 2.0)).abs() < 1e-9);
+        assert!((distance_to_rounded_rect(rect, 10.0, (-2.0, -6.0)) - 10.0).abs() < 1e-9);
+        assert!((distance_to_rounded_rect(rect, 0.0, (-3.0, -4.0)) - 5.0).abs() < 1e-9);
+    }
+
+    fn square(min: f64, max: f64) -> Vec<(f64, f64)> {
+        vec![(min, min), (max, min), (max, max), (min, max)]
+    }
+
+    #[test]
+    fn self_overlapping_outline() {
+        // a pentagram, its outline turns twice around the center
+        let star: Vec<_> = (0..5)
+            .map(|i| {
+                let angle =
+                    -std::f64::consts::FRAC_PI_2 + i as f64 * 4.0 * std::f64::consts::PI / 5.0;
+                (100.0 * angle.cos(), 100.0 * angle.sin())
+            })
+            .collect();
+        let rings = [star];
+
+        assert_eq!(winding_number(&rings[0], (0.0, 0.0)).abs(), 2);
+        assert!(is_point_in_rings(&rings, (0.0, 0.0), FillRule::NonZero));
+        assert!(!is_point_in_rings(&rings, (0.0, 0.0), FillRule::EvenOdd));
+        // the tips are turned around once
+        for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            assert!(is_point_in_rings(&rings, (0.0, -80.0), fill_rule));
+            assert!(!is_point_in_rings(&rings, (0.0, -120.0), fill_rule));
+        }
+    }
+
+    #[test]
+    fn nested_rings() {
+        let outer = square(0.0, 100.0);
+        let inner = square(25.0, 75.0);
+        let reversed: Vec<_> = inner.iter().rev().copied().collect();
+
+        let same = [outer.clone(), inner];
+        assert!(is_point_in_rings(&same, (50.0, 50.0), FillRule::NonZero));
+        assert!(!is_point_in_rings(&same, (50.0, 50.0), FillRule::EvenOdd));
+
+        let opposite = [outer, reversed];
+        assert!(!is_point_in_rings(
+            &opposite,
+            (50.0, 50.0),
+            FillRule::NonZero
+        ));
+        assert!(!is_point_in_rings(
+            &opposite,
+            (50.0, 50.0),
+            FillRule::EvenOdd
+        ));
+
+        for rings in [&same, &opposite] {
+            for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+                assert!(is_point_in_rings(rings, (10.0, 10.0), fill_rule));
+                assert!(!is_point_in_rings(rings, (110.0, 50.0), fill_rule));
+            }
+        }
+    }
+
+    #[test]
+    fn holes_exclude_whichever_the_fill_rule() {
+        // the hole turns the same way as the ring, which NonZero alone would fill
+        let shape = Shape::Polygon {
+            rings: vec![square(0.0, 100.0)],
+            holes: vec![square(25.0, 75.0)],
+        };
+
+        for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            assert!(!shape.contains((50.0, 50.0), fill_rule));
+            assert!(shape.contains((10.0, 10.0), fill_rule));
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use crate::error::{Error, Result};
+use crate::polygon::{FillRule, Shape};
+
+/// Check that the shape can be hit tested reliably.
+///
+/// If `normalize` is set, duplicate consecutive vertices and explicit closing vertices
+/// are removed before checking, instead of being reported as errors.
+/// Self-intersections are only rejected for `FillRule::EvenOdd`, see `check_fill_rule`.
+pub fn validate(shape: Shape, fill_rule: FillRule, normalize: bool) -> Result<Shape> {
+    let shape = validate_shape(shape, normalize)?;
+    check_fill_rule(&shape, fill_rule)?;
+    Ok(shape)
+}
+
+/// Check that a valid shape can be filled with the rule, so a shape accepted under one rule
+/// is not kept under another one which would have rejected it.
+///
+/// Self-intersections are only rejected for `FillRule::EvenOdd`,
+/// since `FillRule::NonZero` is meant for self-overlapping outlines.
+pub fn check_fill_rule(shape: &Shape, fill_rule: FillRule) -> Result<()> {
+    let Shape::Polygon { rings, holes } = shape else {
+        return Ok(());
+    };
+    if fill_rule == FillRule::NonZero {
+        return Ok(());
+    }
+
+    for (kind, rings) in [("ring", rings), ("hole", holes)] {
+        for (i, ring) in rings.iter().enumerate() {
+            if let Some((a, b)) = find_self_intersection(ring) {
+                return Err(Error::SelfIntersecting(format!(
+                    "Edge {a} crosses edge {b} of {kind} {i}."
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_shape(shape: Shape, normalize: bool) -> Result<Shape> {
+    match shape {
+        Shape::Polygon { rings, holes } => {
+            if rings.is_empty() {
+                return Err(Error::PointsNotEnough(0));
+            }
+
+            let check = |kind: &str, rings: Vec<Vec<(f64, f64)>>| {
+                rings
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, ring)| validate_ring(ring, &format!("{kind} {i}"), normalize))
+                    .collect::<Result<Vec<_>>>()
+            };
+
+            Ok(Shape::Polygon {
+                rings: check("ring", rings)?,
+                holes: check("hole", holes)?,
+            })
+        }
+        Shape::Circle { center, radius } => {
+            check_finite(&[center.0, center.1, radius], "circle")?;
+            check_positive(&[radius], "circle radius")?;
+            Ok(Shape::Circle { center, radius })
+        }
+        Shape::Ellipse {
+            center,
+            radius_x,
+            radius_y,
+        } => {
+            check_finite(&[center.0, center.1, radius_x, radius_y], "ellipse")?;
+            check_positive(&[radius_x, radius_y], "ellipse radius")?;
+            Ok(Shape::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            })
+        }
+        Shape::RoundedRect {
+            x,
+            y,
+            width,
+            height,
+            radius,
+        } => {
+            check_finite(&[x, y, width, height, radius], "rounded rect")?;
+            check_positive(&[width, height], "rounded rect size")?;
+            if radius < 0.0 {
+                return Err(Error::DegeneratePolygon(
+                    "Corner radius of rounded rect should not be negative.".into(),
+                ));
+            }
+            Ok(Shape::RoundedRect {
+                x,
+                y,
+                width,
+                height,
+                radius,
+            })
+        }
+    }
+}
+
+fn validate_ring(
+    mut ring: Vec<(f64, f64)>,
+    name: &str,
+    normalize: bool,
+) -> Result<Vec<(f64, f64)>> {
+    if let Some(i) = ring
+        .iter()
+        .position(|(x, y)| !x.is_finite() || !y.is_finite())
+    {
+        return Err(Error::InvalidCoordinate(format!("{name}, vertex {i}")));
+    }
+
+    // rings are closed implicitly, an explicit closing vertex is allowed but redundant
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+
+    if normalize {
+        ring.dedup();
+        // deduplicating may expose another closing vertex
+        while ring.len() > 1 && ring.first() == ring.last() {
+            ring.pop();
+        }
+    } else if let Some(i) = ring.windows(2).position(|w| w[0] == w[1]) {
+        return Err(Error::DuplicateVertex(format!(
+            "{name}, vertex {i} and {}",
+            i + 1
+        )));
+    }
+
+    if ring.len() < 3 {
+        return Err(Error::PointsNotEnough(ring.len()));
+    }
+
+    if is_collinear(&ring) {
+        return Err(Error::DegeneratePolygon(format!(
+            "All vertices of {name} lie on a line, so it has no area."
+        )));
+    }
+
+    Ok(ring)
+}
+
+fn check_finite(values: &[f64], name: &str) -> Result<()> {
+    if values.iter().all(|v| v.is_finite()) {
+        Ok(())
+    } else {
+        Err(Error::InvalidCoordinate(name.into()))
+    }
+}
+
+fn check_positive(values: &[f64], name: &str) -> Result<()> {
+    if values.iter().all(|v| *v > 0.0) {
+        Ok(())
+    } else {
+        Err(Error::DegeneratePolygon(format!(
+            "The {name} should be greater than 0."
+        )))
+    }
+}
+
+fn cross(o: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+fn is_collinear(ring: &[(f64, f64)]) -> bool {
+    let origin = ring[0];
+    let Some(direction) = ring.iter().find(|p| **p != origin) else {
+        return true;
+    };
+    let length = (direction.0 - origin.0).hypot(direction.1 - origin.1);
+
+    // distances to the line are compared against a small fraction of a physical pixel
+    ring.iter()
+        .all(|p| (cross(origin, *direction, *p) / length).abs() < 1e-9)
+}
+
+/// Returns indices of a pair of non-adjacent edges that touch or cross,
+/// edge `i` goes from vertex `i` to vertex `i + 1`.
+///
+/// Edges are swept from left to right, so each edge is only compared with the edges
+/// overlapping it horizontally, instead of with all others. Flattened curves have
+/// thousands of short edges, of which only a few overlap at any x.
+fn find_self_intersection(ring: &[(f64, f64)]) -> Option<(usize, usize)> {
+    let n = ring.len();
+    let edge = |i: usize| (ring[i], ring[(i + 1) % n]);
+    let min_x = |i: usize| ring[i].0.min(ring[(i + 1) % n].0);
+    let max_x = |i: usize| ring[i].0.max(ring[(i + 1) % n].0);
+    // adjacent edges always share a vertex
+    let adjacent = |i: usize, j: usize| {
+        let (i, j) = (i.min(j), i.max(j));
+        j == i + 1 || (i == 0 && j == n - 1)
+    };
+
+    let mut edges = (0..n).collect::<Vec<usize>>();
+    edges.sort_by(|a, b| min_x(*a).total_cmp(&min_x(*b)));
+
+    // edges reaching the left end of the current edge
+    let mut active: Vec<usize> = Vec::new();
+    for i in edges {
+        active.retain(|j| max_x(*j) >= min_x(i));
+
+        let (a, b) = edge(i);
+        for &j in active.iter() {
+            let (c, d) = edge(j);
+            if adjacent(i, j) || a.1.max(b.1) < c.1.min(d.1) || c.1.max(d.1) < a.1.min(b.1) {
+                continue;
+            }
+            if segments_intersect(a, b, c, d) {
+                return Some((i.min(j), i.max(j)));
+            }
+        }
+        active.push(i);
+    }
+
+    // adjacent edges folding back onto each other also overlap
+    for i in 0..n {
+        let (a, b) = edge(i);
+        let c = ring[(i + 2) % n];
+        if n > 3
+            && cross(a, b, c) == 0.0
+            && (c.0 - b.0) * (a.0 - b.0) + (c.1 - b.1) * (a.1 - b.1) > 0.0
+        {
+            return Some((i, (i + 1) % n));
+        }
+    }
+
+    None
+}
+
+fn segments_intersect(a: (f64, f64), b: (f64, f64), c: (f64, f64), d: (f64, f64)) -> bool {
+    let d1 = cross(c, d, a);
+    let d2 = cross(c, d, b);
+    let d3 = cross(a, b, c);
+    let d4 = cross(a, b, d);
+
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+
+    let on_segment = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        r.0 >= p.0.min(q.0) && r.0 <= p.0.max(q.0) && r.1 >= p.1.min(q.1) && r.1 <= p.1.max(q.1)
+    };
+
+    (d1 == 0.0 && on_segment(c, d, a))
+        || (d2 == 0.0 && on_segment(c, d, b))
+        || (d3 == 0.0 && on_segment(a, b, c))
+        || (d4 == 0.0 && on_segment(a, b, d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(f64, f64)]) -> Shape {
+        Shape::Polygon {
+            rings: vec![points.to_vec()],
+            holes: Vec::new(),
+        }
+    }
+
+    const SQUARE: [(f64, f64); 4] = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+    const BOW_TIE: [(f64, f64); 4] = [(0.0, 0.0), (10.0, 10.0), (10.0, 0.0), (0.0, 10.0)];
+
+    #[test]
+    fn valid_shapes() {
+        assert_eq!(
+            validate(ring(&SQUARE), FillRule::EvenOdd, false).unwrap(),
+            ring(&SQUARE)
+        );
+        // an explicit closing vertex is dropped
+        let closed = [&SQUARE[..], &[(0.0, 0.0)]].concat();
+        assert_eq!(
+            validate(ring(&closed), FillRule::EvenOdd, false).unwrap(),
+            ring(&SQUARE)
+        );
+        let circle = Shape::Circle {
+            center: (5.0, 5.0),
+            radius: 5.0,
+        };
+        assert_eq!(
+            validate(circle.clone(), FillRule::EvenOdd, false).unwrap(),
+            circle
+        );
+    }
+
+    #[test]
+    fn invalid_coordinates() {
+        for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let mut points = SQUARE;
+            points[2].1 = bad;
+            assert!(matches!(
+                validate(ring(&points), FillRule::EvenOdd, true),
+                Err(Error::InvalidCoordinate(e)) if e == "ring 0, vertex 2"
+            ));
+            assert!(matches!(
+                validate(
+                    Shape::Circle {
+                        center: (bad, 0.0),
+                        radius: 1.0,
+                    },
+                    FillRule::EvenOdd,
+                    false,
+                ),
+                Err(Error::InvalidCoordinate(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn degenerate_shapes() {
+        assert!(matches!(
+            validate(
+                ring(&[(0.0, 0.0), (5.0, 5.0), (10.0, 10.0)]),
+                FillRule::EvenOdd,
+                false
+            ),
+            Err(Error::DegeneratePolygon(_))
+        ));
+        assert!(matches!(
+            validate(ring(&SQUARE[..2]), FillRule::EvenOdd, false),
+            Err(Error::PointsNotEnough(2))
+        ));
+        assert!(matches!(
+            validate(
+                Shape::Polygon {
+                    rings: Vec::new(),
+                    holes: Vec::new(),
+                },
+                FillRule::EvenOdd,
+                false,
+            ),
+            Err(Error::PointsNotEnough(0))
+        ));
+        assert!(matches!(
+            validate(
+                Shape::Ellipse {
+                    center: (0.0, 0.0),
+                    radius_x: 5.0,
+                    radius_y: 0.0,
+                },
+                FillRule::EvenOdd,
+                false,
+            ),
+            Err(Error::DegeneratePolygon(_))
+        ));
+        assert!(matches!(
+            validate(
+                Shape::RoundedRect {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 10.0,
+                    height: 10.0,
+                    radius: -1.0,
+                },
+                FillRule::EvenOdd,
+                false,
+            ),
+            Err(Error::DegeneratePolygon(_))
+        ));
+    }
+
+    #[test]
+    fn duplicate_vertices() {
+        let points = [SQUARE[0], SQUARE[1], SQUARE[1], SQUARE[2], SQUARE[3]];
+        assert!(matches!(
+            validate(ring(&points), FillRule::EvenOdd, false),
+            Err(Error::DuplicateVertex(e)) if e == "ring 0, vertex 1 and 2"
+        ));
+    }
+
+    #[test]
+    fn self_intersections() {
+        assert!(matches!(
+            validate(ring(&BOW_TIE), FillRule::EvenOdd, false),
+            Err(Error::SelfIntersecting(e)) if e == "Edge 0 crosses edge 2 of ring 0."
+        ));
+        // self-overlapping outlines are meant for the non-zero rule
+        assert!(validate(ring(&BOW_TIE), FillRule::NonZero, false).is_ok());
+
+        // touching at a vertex
+        let touching = [
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (5.0, 5.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (5.0, 0.0),
+        ];
+        assert!(matches!(
+            validate(ring(&touching), FillRule::EvenOdd, false),
+            Err(Error::SelfIntersecting(_))
+        ));
+        // folding back onto the previous edge
+        let folded = [(0.0, 0.0), (10.0, 0.0), (5.0, 0.0), (5.0, 10.0)];
+        assert!(matches!(
+            validate(ring(&folded), FillRule::EvenOdd, false),
+            Err(Error::SelfIntersecting(_))
+        ));
+    }
+
+    #[test]
+    fn long_rings() {
+        // a flattened circle, where every edge overlaps its neighbours horizontally
+        let circle = (0..20_000)
+            .map(|i| {
+                let (sin, cos) = (i as f64 / 20_000.0 * std::f64::consts::TAU).sin_cos();
+                (1000.0 * cos, 1000.0 * sin)
+            })
+            .collect::<Vec<(f64, f64)>>();
+        assert!(validate(ring(&circle), FillRule::EvenOdd, false).is_ok());
+
+        // the last edge crosses the first one
+        let mut spiral = circle.clone();
+        spiral.push((1010.0, -10.0));
+        spiral.push((990.0, 10.0));
+        assert!(matches!(
+            validate(ring(&spiral), FillRule::EvenOdd, false),
+            Err(Error::SelfIntersecting(_))
+        ));
+    }
+
+    #[test]
+    fn holes() {
+        let inner = [(2.0, 2.0), (8.0, 2.0), (8.0, 8.0), (2.0, 8.0)];
+        let shape = Shape::Polygon {
+            rings: vec![SQUARE.to_vec()],
+            holes: vec![inner.to_vec()],
+        };
+        assert_eq!(
+            validate(shape.clone(), FillRule::EvenOdd, false).unwrap(),
+            shape
+        );
+
+        let shape = Shape::Polygon {
+            rings: vec![SQUARE.to_vec()],
+            holes: vec![inner.to_vec(), BOW_TIE.to_vec()],
+        };
+        assert!(matches!(
+            validate(shape, FillRule::EvenOdd, false),
+            Err(Error::SelfIntersecting(e)) if e == "Edge 0 crosses edge 2 of hole 1."
+        ));
+
+        let shape = Shape::Polygon {
+            rings: vec![SQUARE.to_vec()],
+            holes: vec![vec![(2.0, 2.0), (f64::NAN, 2.0), (8.0, 8.0)]],
+        };
+        assert!(matches!(
+            validate(shape, FillRule::EvenOdd, false),
+            Err(Error::InvalidCoordinate(e)) if e == "hole 0, vertex 1"
+        ));
+    }
+
+    #[test]
+    fn normalization() {
+        // duplicates are removed, along with the closing vertices they expose
+        let points = [
+            SQUARE[0], SQUARE[0], SQUARE[1], SQUARE[2], SQUARE[2], SQUARE[2], SQUARE[3], SQUARE[0],
+            SQUARE[0],
+        ];
+        assert_eq!(
+            validate(ring(&points), FillRule::EvenOdd, true).unwrap(),
+            ring(&SQUARE)
+        );
+        // what is left is still checked
+        let points = [SQUARE[0], SQUARE[1], SQUARE[1], SQUARE[0]];
+        assert!(matches!(
+            validate(ring(&points), FillRule::EvenOdd, true),
+            Err(Error::PointsNotEnough(2))
+        ));
+        assert!(matches!(
+            validate(ring(&BOW_TIE), FillRule::EvenOdd, true),
+            Err(Error::SelfIntersecting(_))
+        ));
+    }
+}
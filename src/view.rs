@@ -1,153 +1,648 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
 use crate::error::*;
+use crate::index::Grid;
+use crate::models::PolygonInfo;
 use crate::polygon::*;
-use crate::statics::*;
-use crate::utils;
+use crate::registry::Registry;
+use crate::utils::Convert;
+use crate::validation;
 
-pub(crate) fn register(id: PolygonId) -> Result<()> {
-    let mut ids = REGISTERED_IDS.get().ok_or(Error::NotInitialized)?.write()?;
-    if ids.contains(&id) {
+pub(crate) fn register(registry: &Registry, id: PolygonId) -> Result<()> {
+    let mut registered = registry.polygons.write()?;
+    if registered.contains_key(&id) {
         return Err(Error::PolygonExists(id));
     }
-    ids.insert(id.clone());
 
-    let polygon = Polygon::default(&id);
+    let polygon = Polygon::default(&id, registry.next_order());
 
-    let mut registered = REGISTERED_POLYGON
-        .get()
-        .ok_or(Error::NotInitialized)?
-        .write()?;
+    let mut index = registry.index.write()?;
+    index.insert(&id, polygon.bounds());
+    registry.record(&id, Some(&polygon));
     registered.insert(id, polygon);
 
     Ok(())
 }
 
-pub(crate) fn register_all(ids: Vec<PolygonId>) -> Result<()> {
+pub(crate) fn register_all(registry: &Registry, ids: Vec<PolygonId>) -> Result<()> {
     let polygons = ids
         .iter()
-        .map(|id| Polygon::default(id))
+        .map(|id| Polygon::default(id, registry.next_order()))
         .collect::<Vec<Polygon>>();
 
-    let mut registered = REGISTERED_POLYGON
-        .get()
-        .ok_or(Error::NotInitialized)?
-        .write()?;
+    let mut registered = registry.polygons.write()?;
+
+    let mut index = registry.index.write()?;
 
     for polygon in polygons {
+        index.insert(polygon.id(), polygon.bounds());
+        registry.record(polygon.id(), Some(&polygon));
         registered.insert(polygon.id().into(), polygon);
     }
 
-    let mut registered_ids = REGISTERED_IDS.get().ok_or(Error::NotInitialized)?.write()?;
+    Ok(())
+}
 
-    for id in ids.iter() {
-        registered_ids.insert(id.into());
+pub(crate) fn remove(registry: &Registry, id: &str) -> Result<()> {
+    let mut registered = registry.polygons.write()?;
+    if registered.remove(id).is_none() {
+        return Err(Error::PolygonNotFound(id.to_string()));
     }
 
+    registry.index.write()?.remove(id);
+    registry.hovered.write()?.remove(id);
+    registry.record(id, None);
+
     Ok(())
 }
 
-pub(crate) fn remove(id: &str) -> Result<()> {
-    let mut ids = REGISTERED_IDS.get().ok_or(Error::NotInitialized)?.write()?;
-    if !ids.contains(&id.to_string()) {
-        return Err(Error::PolygonNotFound(id.to_string()));
+pub(crate) fn clear(registry: &Registry) -> Result<()> {
+    let mut registered = registry.polygons.write()?;
+
+    // removals are written in creation order, so recordings do not depend on the map order
+    let mut removed = registered
+        .drain()
+        .map(|(_, polygon)| polygon)
+        .collect::<Vec<Polygon>>();
+    removed.sort_by_key(|polygon| polygon.priority().1);
+
+    registry.index.write()?.clear();
+    registry.hovered.write()?.clear();
+    for polygon in removed {
+        registry.record(polygon.id(), None);
     }
 
-    let mut registered = REGISTERED_POLYGON
-        .get()
-        .ok_or(Error::NotInitialized)?
-        .write()?;
+    Ok(())
+}
+
+pub(crate) fn update_with_holes(
+    registry: &Registry,
+    id: &str,
+    rings: &[Vec<(f64, f64)>],
+    holes: &[Vec<(f64, f64)>],
+    normalize: bool,
+) -> Result<()> {
+    update_shape(
+        registry,
+        id,
+        Shape::Polygon {
+            rings: rings.to_vec(),
+            holes: holes.to_vec(),
+        },
+        normalize,
+    )
+}
 
-    if let Some(polygon) = registered.remove(id) {
-        polygon.distroy();
+/// With `normalize`, points are cleaned up before validation instead of being rejected.
+pub(crate) fn update_shape(
+    registry: &Registry,
+    id: &str,
+    shape: Shape,
+    normalize: bool,
+) -> Result<()> {
+    update_shape_with(registry, id, shape, None, None, normalize)
+}
+
+/// Like `update_shape`, along with the fill rule and padding if given.
+/// The shape is validated under the new fill rule, and nothing is changed if it is invalid.
+pub(crate) fn update_shape_with(
+    registry: &Registry,
+    id: &str,
+    shape: Shape,
+    fill_rule: Option<FillRule>,
+    padding: Option<f64>,
+    normalize: bool,
+) -> Result<()> {
+    if padding.is_some_and(|padding| !padding.is_finite()) {
+        return Err(Error::InvalidCoordinate("padding".into()));
     }
 
-    ids.retain(|i| i != id);
+    let registered = registry.polygons.read()?;
+
+    if let Some(polygon) = registered.get(id) {
+        polygon.update_geometry(|geometry| {
+            let fill_rule = fill_rule.unwrap_or(geometry.fill_rule);
+            Ok::<_, Error>(Geometry::new(
+                validation::validate(shape.clone(), fill_rule, normalize)?,
+                padding.unwrap_or(geometry.padding),
+                fill_rule,
+            ))
+        })?;
+        registry.index.write()?.insert(id, polygon.bounds());
+        registry.record(id, Some(polygon));
+        Ok(())
+    } else {
+        Err(Error::PolygonNotFound(id.to_string()))
+    }
+}
 
+/// Map the current shape of the polygon through `t`, the result is validated like an update.
+pub(crate) fn transform(
+    registry: &Registry,
+    id: &str,
+    t: &Transform,
+    normalize: bool,
+) -> Result<()> {
+    if !t.is_finite() {
+        return Err(Error::InvalidCoordinate("transform".into()));
+    }
+
+    let registered = registry.polygons.read()?;
+    let polygon = registered
+        .get(id)
+        .ok_or_else(|| Error::PolygonNotFound(id.to_string()))?;
+
+    // concurrent transforms are applied one after another, instead of overwriting each other
+    polygon.update_geometry(|geometry| {
+        Ok::<_, Error>(Geometry::new(
+            validation::validate(geometry.shape.transform(t), geometry.fill_rule, normalize)?,
+            geometry.padding,
+            geometry.fill_rule,
+        ))
+    })?;
+    registry.index.write()?.insert(id, polygon.bounds());
+    registry.record(id, Some(polygon));
     Ok(())
 }
 
-pub(crate) fn clear() -> Result<()> {
-    let mut registered = REGISTERED_POLYGON
-        .get()
-        .ok_or(Error::NotInitialized)?
-        .write()?;
+/// Convert every polygon from the units of `from` to those of `to`, along with its padding,
+/// the way `Polygon::update_*` convert their arguments.
+/// The results are validated like an update, and a polygon which fails is left as it is.
+pub(crate) fn reproject(registry: &Registry, from: &Convert, to: &Convert) -> Result<()> {
+    let registered = registry.polygons.read()?;
+    let mut index = registry.index.write()?;
+    let mut result = Ok(());
 
-    for (_, polygon) in registered.iter_mut() {
-        polygon.distroy();
+    // in creation order, so recordings do not depend on the map order
+    let mut polygons = registered.values().collect::<Vec<&Polygon>>();
+    polygons.sort_by_key(|polygon| polygon.priority().1);
+    for polygon in polygons {
+        let reprojected = polygon.update_geometry(|geometry| {
+            let shape = to.shape_from_viewport(&from.shape_to_viewport(&geometry.shape));
+            Ok::<_, Error>(Geometry::new(
+                validation::validate(shape, geometry.fill_rule, false)?,
+                to.length_from_viewport(from.length_to_viewport(geometry.padding)),
+                geometry.fill_rule,
+            ))
+        });
+        match reprojected {
+            Ok(()) => {
+                index.insert(polygon.id(), polygon.bounds());
+                registry.record(polygon.id(), Some(polygon));
+            }
+            Err(e) if result.is_ok() => result = Err(e),
+            Err(_) => {}
+        }
     }
 
-    registered.clear();
+    result
+}
 
-    let mut ids = REGISTERED_IDS.get().ok_or(Error::NotInitialized)?.write()?;
-    ids.clear();
+/// The current shape is checked against the new rule, and kept under the old one if it fails.
+pub(crate) fn set_fill_rule(registry: &Registry, id: &str, fill_rule: FillRule) -> Result<()> {
+    let registered = registry.polygons.read()?;
 
-    Ok(())
+    if let Some(polygon) = registered.get(id) {
+        polygon.update_geometry(|geometry| {
+            validation::check_fill_rule(&geometry.shape, fill_rule)?;
+            Ok::<_, Error>(Geometry::new(
+                geometry.shape.clone(),
+                geometry.padding,
+                fill_rule,
+            ))
+        })?;
+        registry.record(id, Some(polygon));
+        Ok(())
+    } else {
+        Err(Error::PolygonNotFound(id.to_string()))
+    }
 }
 
-pub(crate) fn update(id: &str, points: &[(f64, f64)]) -> Result<()> {
-    if points.len() < 3 {
-        return Err(Error::PointsNotEnough(points.len()));
+pub(crate) fn set_padding(registry: &Registry, id: &str, padding: f64) -> Result<()> {
+    if !padding.is_finite() {
+        return Err(Error::InvalidCoordinate("padding".into()));
     }
 
-    let registered = REGISTERED_POLYGON
-        .get()
-        .ok_or(Error::NotInitialized)?
-        .read()?;
+    let registered = registry.polygons.read()?;
 
     if let Some(polygon) = registered.get(id) {
-        polygon.set_points(points);
+        polygon.set_padding(padding);
+        registry.index.write()?.insert(id, polygon.bounds());
+        registry.record(id, Some(polygon));
         Ok(())
     } else {
         Err(Error::PolygonNotFound(id.to_string()))
     }
 }
 
-pub(crate) fn hide(id: &str) -> Result<()> {
-    let registered = REGISTERED_POLYGON
-        .get()
-        .ok_or(Error::NotInitialized)?
-        .read()?;
+pub(crate) fn set_z_index(registry: &Registry, id: &str, z_index: i32) -> Result<()> {
+    let registered = registry.polygons.read()?;
+
+    if let Some(polygon) = registered.get(id) {
+        polygon.set_z_index(z_index);
+        registry.record(id, Some(polygon));
+        Ok(())
+    } else {
+        Err(Error::PolygonNotFound(id.to_string()))
+    }
+}
+
+/// Put the polygon above all others.
+pub(crate) fn raise(registry: &Registry, id: &str) -> Result<()> {
+    let registered = registry.polygons.read()?;
+
+    let polygon = registered
+        .get(id)
+        .ok_or_else(|| Error::PolygonNotFound(id.to_string()))?;
+    let top = registered
+        .values()
+        .filter(|p| p.id() != id)
+        .map(Polygon::z_index)
+        .max();
+    if let Some(top) = top.filter(|top| *top >= polygon.z_index()) {
+        polygon.set_z_index(top.saturating_add(1));
+        registry.record(id, Some(polygon));
+    }
+    Ok(())
+}
+
+/// Put the polygon below all others.
+pub(crate) fn lower(registry: &Registry, id: &str) -> Result<()> {
+    let registered = registry.polygons.read()?;
+
+    let polygon = registered
+        .get(id)
+        .ok_or_else(|| Error::PolygonNotFound(id.to_string()))?;
+    let bottom = registered
+        .values()
+        .filter(|p| p.id() != id)
+        .map(Polygon::z_index)
+        .min();
+    if let Some(bottom) = bottom.filter(|bottom| *bottom <= polygon.z_index()) {
+        polygon.set_z_index(bottom.saturating_sub(1));
+        registry.record(id, Some(polygon));
+    }
+    Ok(())
+}
+
+pub(crate) fn hide(registry: &Registry, id: &str) -> Result<()> {
+    let registered = registry.polygons.read()?;
 
     if let Some(polygon) = registered.get(id) {
         polygon.hide();
+        registry.record(id, Some(polygon));
         Ok(())
     } else {
         Err(Error::PolygonNotFound(id.to_string()))
     }
 }
 
-pub(crate) fn show(id: &str) -> Result<()> {
-    let registered = REGISTERED_POLYGON
-        .get()
-        .ok_or(Error::NotInitialized)?
-        .read()?;
+pub(crate) fn show(registry: &Registry, id: &str) -> Result<()> {
+    let registered = registry.polygons.read()?;
 
     if let Some(polygon) = registered.get(id) {
         polygon.show();
+        registry.record(id, Some(polygon));
         Ok(())
     } else {
         Err(Error::PolygonNotFound(id.to_string()))
     }
 }
 
-pub(crate) fn cursor_in() -> Result<Vec<PolygonId>> {
-    let mut v = Vec::new();
-    let registered = REGISTERED_POLYGON
-        .get()
-        .ok_or(Error::NotInitialized)?
-        .read()?;
+/// Hit test the cursor position against polygons near it,
+/// and update the `cursor_in` state of polygons it enters or leaves.
+/// Returns ids of polygons the cursor is in, the topmost first.
+pub(crate) fn cursor_moved(registry: &Registry, x: f64, y: f64) -> Result<Vec<PolygonId>> {
+    let registered = registry.polygons.read()?;
+    let index = registry.index.read()?;
+    let mut hovered = registry.hovered.write()?;
+
+    let ids = hits(&registered, &index, x, y);
 
-    for (id, polygon) in registered.iter() {
-        if polygon.cursor_in() {
-            v.push(id.clone());
+    for id in hovered.iter().filter(|id| !ids.contains(id)) {
+        if let Some(polygon) = registered.get(id) {
+            polygon.set_cursor_in(false);
         }
     }
-    Ok(v)
+    hovered.clear();
+
+    for id in ids.iter() {
+        if let Some(polygon) = registered.get(id) {
+            polygon.set_cursor_in(true);
+        }
+        hovered.insert(id.clone());
+    }
+
+    Ok(ids)
 }
 
-pub(crate) fn pos_contained(polygon: &Polygon, x: f64, y: f64) -> bool {
-    if polygon.display() {
-        utils::is_point_in_polygon(&polygon.points(), (x, y))
+pub(crate) fn exists(registry: &Registry, id: &str) -> Result<bool> {
+    let registered = registry.polygons.read()?;
+    Ok(registered.contains_key(id))
+}
+
+/// Info of the polygon, in physical pixels.
+pub(crate) fn get(registry: &Registry, id: &str) -> Result<PolygonInfo> {
+    let registered = registry.polygons.read()?;
+
+    registered
+        .get(id)
+        .map(info)
+        .ok_or_else(|| Error::PolygonNotFound(id.to_string()))
+}
+
+/// Info of all polygons in physical pixels, the topmost first.
+pub(crate) fn list(registry: &Registry) -> Result<Vec<PolygonInfo>> {
+    let registered = registry.polygons.read()?;
+
+    let mut polygons = registered.values().collect::<Vec<&Polygon>>();
+    polygons.sort_by_cached_key(|polygon| Reverse(polygon.priority()));
+    Ok(polygons.into_iter().map(info).collect())
+}
+
+fn info(polygon: &Polygon) -> PolygonInfo {
+    let geometry = polygon.geometry();
+    PolygonInfo {
+        id: polygon.id().to_string(),
+        shape: geometry.shape.clone(),
+        visible: polygon.display(),
+        hovered: polygon.cursor_in(),
+        fill_rule: polygon.fill_rule(),
+        padding: geometry.padding,
+        z_index: polygon.z_index(),
+        metadata: polygon.metadata(),
+    }
+}
+
+pub(crate) fn set_metadata(
+    registry: &Registry,
+    id: &str,
+    metadata: Option<serde_json::Value>,
+) -> Result<()> {
+    let registered = registry.polygons.read()?;
+
+    if let Some(polygon) = registered.get(id) {
+        polygon.set_metadata(metadata);
+        Ok(())
     } else {
-        false
+        Err(Error::PolygonNotFound(id.to_string()))
+    }
+}
+
+/// The cursor is away from all polygons, e.g. on another monitor.
+pub(crate) fn cursor_left(registry: &Registry) -> Result<()> {
+    let registered = registry.polygons.read()?;
+    let mut hovered = registry.hovered.write()?;
+
+    for id in hovered.drain() {
+        if let Some(polygon) = registered.get(&id) {
+            polygon.set_cursor_in(false);
+        }
+    }
+    Ok(())
+}
+
+/// Ids of visible polygons containing the point, the topmost first.
+/// Unlike `cursor_moved`, no state is changed.
+pub(crate) fn hit_test(registry: &Registry, x: f64, y: f64) -> Result<Vec<PolygonId>> {
+    let registered = registry.polygons.read()?;
+    let index = registry.index.read()?;
+
+    Ok(hits(&registered, &index, x, y))
+}
+
+fn hits(registered: &HashMap<PolygonId, Polygon>, index: &Grid, x: f64, y: f64) -> Vec<PolygonId> {
+    let mut ids = index
+        .query((x, y))
+        .filter(|id| {
+            registered
+                .get(*id)
+                .is_some_and(|polygon| pos_contained(polygon, x, y))
+        })
+        .cloned()
+        .collect::<Vec<PolygonId>>();
+    sort_by_priority(registered, &mut ids);
+    ids
+}
+
+/// Sort ids so that polygons on top come first.
+fn sort_by_priority(registered: &HashMap<PolygonId, Polygon>, ids: &mut [PolygonId]) {
+    ids.sort_by_cached_key(|id| Reverse(registered.get(id).map(Polygon::priority)));
+}
+
+/// Points inside a hole of the polygon are not contained.
+/// Padding of the polygon is taken into account.
+pub(crate) fn pos_contained(polygon: &Polygon, x: f64, y: f64) -> bool {
+    if !polygon.display() {
+        return false;
+    }
+
+    polygon.geometry().contains((x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        sync::Arc,
+        thread,
+    };
+
+    use super::*;
+    use crate::utils::CoordinateMode;
+
+    fn square(size: f64) -> Shape {
+        Shape::Polygon {
+            rings: vec![vec![(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)]],
+            holes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn invalid_update_keeps_fill_rule_and_padding() {
+        let registry = Registry::new("main", Arc::default());
+        register(&registry, "bow-tie".into()).unwrap();
+        update_shape(&registry, "bow-tie", square(10.0), false).unwrap();
+
+        let bow_tie = Shape::Polygon {
+            rings: vec![vec![(0.0, 0.0), (10.0, 10.0), (10.0, 0.0), (0.0, 10.0)]],
+            holes: Vec::new(),
+        };
+        // self-intersections are only rejected with the even-odd rule
+        let result = update_shape_with(
+            &registry,
+            "bow-tie",
+            bow_tie.clone(),
+            Some(FillRule::EvenOdd),
+            Some(5.0),
+            false,
+        );
+        assert!(matches!(result, Err(Error::SelfIntersecting(_))));
+        let geometry = registry.polygons.read().unwrap()["bow-tie"].geometry();
+        assert_eq!(geometry.shape, square(10.0));
+        assert_eq!(geometry.padding, 0.0);
+
+        update_shape_with(
+            &registry,
+            "bow-tie",
+            bow_tie.clone(),
+            Some(FillRule::NonZero),
+            Some(5.0),
+            false,
+        )
+        .unwrap();
+        let geometry = registry.polygons.read().unwrap()["bow-tie"].geometry();
+        assert_eq!(geometry.shape, bow_tie);
+        assert_eq!(geometry.fill_rule, FillRule::NonZero);
+        assert_eq!(geometry.padding, 5.0);
+    }
+
+    #[test]
+    fn concurrent_transforms_are_not_lost() {
+        let registry = Arc::new(Registry::new("main", Arc::default()));
+        register(&registry, "moving".into()).unwrap();
+        update_shape(&registry, "moving", square(10.0), false).unwrap();
+
+        let movers = (0..4)
+            .map(|_| {
+                let registry = registry.clone();
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        transform(&registry, "moving", &Transform::translate(1.0, 0.0), false)
+                            .unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for mover in movers {
+            mover.join().unwrap();
+        }
+
+        let polygon = &registry.polygons.read().unwrap()["moving"];
+        assert_eq!(
+            polygon.geometry().shape,
+            square(10.0).transform(&Transform::translate(4000.0, 0.0))
+        );
+        // the index follows the last transform
+        assert!(registry
+            .index
+            .read()
+            .unwrap()
+            .query((4005.0, 5.0))
+            .any(|id| id == "moving"));
+    }
+
+    #[test]
+    fn concurrent_update_and_hit_test() {
+        let registry = Arc::new(Registry::new("main", Arc::default()));
+        register(&registry, "stress".into()).unwrap();
+        update_shape(&registry, "stress", square(10.0), false).unwrap();
+        show(&registry, "stress").unwrap();
+        let running = Arc::new(AtomicBool::new(true));
+
+        let readers = (0..4)
+            .map(|_| {
+                let registry = registry.clone();
+                let running = running.clone();
+                thread::spawn(move || {
+                    let mut checks = 0u64;
+                    while running.load(Ordering::SeqCst) {
+                        let registered = registry.polygons.read().unwrap();
+                        let polygon = &registered["stress"];
+                        let geometry = polygon.geometry();
+                        // bounds always belong to the shape of the same snapshot
+                        assert_eq!(geometry.bounds, geometry.shape.bounds());
+                        // every square written contains this point
+                        assert!(pos_contained(polygon, 5.0, 5.0));
+                        checks += 1;
+                    }
+                    checks
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let writers = (0..2)
+            .map(|n| {
+                let registry = registry.clone();
+                thread::spawn(move || {
+                    for i in 0..20_000 {
+                        let shape = match (i + n) % 3 {
+                            0 => square(10.0 + (i % 100) as f64),
+                            1 => Shape::Circle {
+                                center: (5.0, 5.0),
+                                radius: 1.0 + (i % 50) as f64,
+                            },
+                            _ => Shape::Polygon {
+                                rings: vec![
+                                    vec![(0.0, 0.0), (20.0, 0.0), (20.0, 20.0), (0.0, 20.0)],
+                                    vec![(30.0, 30.0), (40.0, 30.0), (40.0, 40.0)],
+                                ],
+                                holes: vec![vec![(15.0, 15.0), (18.0, 15.0), (18.0, 18.0)]],
+                            },
+                        };
+                        update_shape(&registry, "stress", shape, false).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        running.store(false, Ordering::SeqCst);
+        for reader in readers {
+            assert!(reader.join().unwrap() > 0);
+        }
+    }
+
+    #[test]
+    fn fill_rule_is_checked_against_the_shape() {
+        let registry = Registry::new("main", Arc::default());
+        register(&registry, "bow-tie".into()).unwrap();
+        // registered polygons can be given a fill rule before their shape
+        set_fill_rule(&registry, "bow-tie", FillRule::NonZero).unwrap();
+        let bow_tie = Shape::Polygon {
+            rings: vec![vec![(0.0, 0.0), (10.0, 10.0), (10.0, 0.0), (0.0, 10.0)]],
+            holes: Vec::new(),
+        };
+        update_shape(&registry, "bow-tie", bow_tie, false).unwrap();
+
+        assert!(matches!(
+            set_fill_rule(&registry, "bow-tie", FillRule::EvenOdd),
+            Err(Error::SelfIntersecting(_))
+        ));
+        assert_eq!(
+            registry.polygons.read().unwrap()["bow-tie"].fill_rule(),
+            FillRule::NonZero
+        );
+    }
+
+    #[test]
+    fn reprojected_shapes_keep_their_kind() {
+        let registry = Registry::new("main", Arc::default());
+        register(&registry, "dot".into()).unwrap();
+        let circle = Shape::Circle {
+            center: (500.0, 250.0),
+            radius: 100.0,
+        };
+        update_shape_with(&registry, "dot", circle, None, Some(10.0), false).unwrap();
+
+        registry.frame.set_size(1000.0, 500.0, 1.0);
+        let from = Convert::new(&registry.frame, CoordinateMode::Fraction);
+        registry.frame.set_size(2000.0, 2000.0, 1.0);
+        let to = Convert::new(&registry.frame, CoordinateMode::Fraction);
+        reproject(&registry, &from, &to).unwrap();
+
+        // radii and padding follow the width, like `update_circle` converts them
+        let geometry = registry.polygons.read().unwrap()["dot"].geometry();
+        assert_eq!(
+            geometry.shape,
+            Shape::Circle {
+                center: (1000.0, 1000.0),
+                radius: 200.0,
+            }
+        );
+        assert_eq!(geometry.padding, 20.0);
     }
 }